@@ -0,0 +1,42 @@
+//! Compares validating a submission with a freshly compiled schema, as every
+//! request did before, against one taken from the `SchemaCache`.
+//!
+//! Run with `cargo bench --bench schema_cache`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use serde_json::json;
+
+use gearsfn::qna::schemacache::{self, SchemaCache};
+use gearsfn::qna::{questionlist, validation};
+
+fn validate(c: &mut Criterion) {
+    let sample = questionlist::sample_string_questions();
+    let answers = json!({
+        "1": "Ice",
+        "8": ["Ice", "Cream"],
+        "88": 10,
+        "222": true,
+        "24": "zork",
+        "3": "Some text",
+        "899": true,
+        "dependants": [{ "name": "Ann", "age": 7, "relationship": "child" }],
+    });
+
+    c.bench_function("compile per request", |b| {
+        b.iter(|| {
+            let compiled = schemacache::compile(&sample).unwrap();
+            validation::validate_with_schema(&sample, &compiled, &answers).unwrap();
+        })
+    });
+
+    let cache = SchemaCache::new();
+    c.bench_function("cached schema", |b| {
+        b.iter(|| {
+            let compiled = cache.get("sample", &sample).unwrap();
+            validation::validate_with_schema(&sample, &compiled, &answers).unwrap();
+        })
+    });
+}
+
+criterion_group!(benches, validate);
+criterion_main!(benches);
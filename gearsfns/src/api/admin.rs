@@ -0,0 +1,966 @@
+use chrono::Utc;
+use lambda_http::Request;
+use serde_json::{json, to_value};
+use uuid::Uuid;
+
+use super::{path_segments, respond, ApiResponse};
+use crate::qna::lint::{self, Diagnostic};
+use crate::qna::migration::{self, Plan, Rule};
+use crate::qna::questionlist::QuestionList;
+use crate::qna::schemaimport;
+use crate::store::{Revision, Stores, Submission};
+use crate::Error;
+
+enum Route<'a> {
+    Collection,
+    Item(&'a str),
+    Keys(&'a str),
+    Revisions(&'a str),
+    Revision(&'a str, u32),
+    Publish(&'a str, u32),
+    Published(&'a str),
+    Migrations(&'a str),
+    Import,
+}
+
+fn route(req: &Request) -> Option<Route<'_>> {
+    match path_segments(req).as_slice() {
+        ["admin", "questionnaires"] => Some(Route::Collection),
+        ["admin", "questionnaires", id] => Some(Route::Item(id)),
+        ["admin", "questionnaires", id, "keys"] => Some(Route::Keys(id)),
+        ["admin", "questionnaires", id, "revisions"] => Some(Route::Revisions(id)),
+        ["admin", "questionnaires", id, "revisions", version] => version
+            .parse()
+            .ok()
+            .map(|version| Route::Revision(id, version)),
+        ["admin", "questionnaires", id, "revisions", version, "publish"] => version
+            .parse()
+            .ok()
+            .map(|version| Route::Publish(id, version)),
+        ["admin", "questionnaires", id, "published"] => Some(Route::Published(id)),
+        ["admin", "questionnaires", id, "migrations"] => Some(Route::Migrations(id)),
+        ["admin", "imports"] => Some(Route::Import),
+        _ => None,
+    }
+}
+
+/// Parses the request body with the `deny_unknown_fields` questionnaire
+/// model, so nothing that would not round-trip is ever stored.
+fn parse_question_list(req: &Request) -> Result<QuestionList, ApiResponse> {
+    serde_json::from_slice(req.body().as_ref()).map_err(|err| {
+        ApiResponse::with_body(
+            400,
+            json!({
+                "message": "invalid questionnaire",
+                "errors": [err.to_string()],
+            }),
+        )
+    })
+}
+
+/// Lints a questionnaire about to be saved, rejecting it on any error and
+/// passing the remaining warnings back to the author.
+fn lint_for_save(question_list: &QuestionList) -> Result<Vec<Diagnostic>, ApiResponse> {
+    let diagnostics = lint::lint(question_list);
+    if lint::has_errors(&diagnostics) {
+        Err(ApiResponse::invalid_questionnaire(422, &diagnostics))
+    } else {
+        Ok(diagnostics)
+    }
+}
+
+pub async fn router(req: Request, stores: &Stores) -> Result<ApiResponse, Error> {
+    match (req.method().as_str(), route(&req)) {
+        ("GET", Some(Route::Collection)) => list(stores).await,
+        ("POST", Some(Route::Collection)) => create(&req, stores).await,
+        ("GET", Some(Route::Item(id))) => read(id, stores).await,
+        ("PUT", Some(Route::Item(id))) => update(id, &req, stores).await,
+        ("DELETE", Some(Route::Item(id))) => delete(id, stores).await,
+        ("GET", Some(Route::Keys(id))) => keys(id, stores).await,
+        ("GET", Some(Route::Revisions(id))) => revisions(id, stores).await,
+        ("GET", Some(Route::Revision(id, version))) => revision(id, version, stores).await,
+        ("POST", Some(Route::Publish(id, version))) => publish(id, version, stores).await,
+        ("GET", Some(Route::Published(id))) => published(id, stores).await,
+        ("POST", Some(Route::Migrations(id))) => migrate(id, &req, stores).await,
+        ("POST", Some(Route::Import)) => import(&req, stores).await,
+        (_, Some(_)) => Ok(ApiResponse::method_not_allowed()),
+        (_, None) => Ok(ApiResponse::not_found()),
+    }
+}
+
+/// Saves `question_list` as a new draft revision and makes it the current
+/// state of questionnaire `id`. Creating the revision fails if another save
+/// took its version first, leaving the current state alone. Should writing
+/// the current state fail instead, the revision stays; the next save is
+/// numbered after it and writes the current state again.
+async fn save(id: &str, question_list: &QuestionList, stores: &Stores) -> Result<(), Error> {
+    stores
+        .revisions
+        .create(&Revision::draft(id, question_list.clone()))
+        .await?;
+    stores.question_lists.put(id, question_list).await?;
+    Ok(())
+}
+
+async fn list(stores: &Stores) -> Result<ApiResponse, Error> {
+    let ids = stores.question_lists.list().await?;
+    Ok(ApiResponse::with_body(
+        200,
+        json!({
+            "questionnaires": ids,
+        }),
+    ))
+}
+
+async fn create(req: &Request, stores: &Stores) -> Result<ApiResponse, Error> {
+    let mut question_list = match parse_question_list(req) {
+        Ok(question_list) => question_list,
+        Err(response) => return Ok(response),
+    };
+    let diagnostics = match lint_for_save(&question_list) {
+        Ok(diagnostics) => diagnostics,
+        Err(response) => return Ok(response),
+    };
+    let id = Uuid::new_v4().to_string();
+    question_list.version = 1;
+    save(&id, &question_list, stores).await?;
+    Ok(ApiResponse::with_body(
+        201,
+        json!({
+            "id": id,
+            "version": question_list.version,
+            "status": "draft",
+            "diagnostics": diagnostics,
+        }),
+    ))
+}
+
+async fn read(id: &str, stores: &Stores) -> Result<ApiResponse, Error> {
+    match stores.question_lists.get(id).await? {
+        Some(question_list) => Ok(ApiResponse::with_body(200, to_value(question_list)?)),
+        None => Ok(ApiResponse::not_found()),
+    }
+}
+
+async fn update(id: &str, req: &Request, stores: &Stores) -> Result<ApiResponse, Error> {
+    let mut question_list = match parse_question_list(req) {
+        Ok(question_list) => question_list,
+        Err(response) => return Ok(response),
+    };
+    let diagnostics = match lint_for_save(&question_list) {
+        Ok(diagnostics) => diagnostics,
+        Err(response) => return Ok(response),
+    };
+    let current = match stores.question_lists.get(id).await? {
+        Some(current) => current,
+        None => return Ok(ApiResponse::not_found()),
+    };
+    let version = current.version;
+    let revisions = stores.revisions.list(id).await?;
+    // Questionnaires saved before revisions were introduced are served as
+    // stored; keep serving that state while the edit is only a draft.
+    if revisions.is_empty() {
+        stores
+            .revisions
+            .create(&Revision::draft(id, current))
+            .await?;
+        stores.revisions.publish(id, version, Utc::now()).await?;
+    }
+    let latest = revisions
+        .last()
+        .map_or(version, |revision| revision.version.max(version));
+    question_list.version = latest + 1;
+    save(id, &question_list, stores).await?;
+    Ok(ApiResponse::with_body(
+        200,
+        json!({
+            "id": id,
+            "version": question_list.version,
+            "status": "draft",
+            "diagnostics": diagnostics,
+        }),
+    ))
+}
+
+/// Removes questionnaire `id` from the list and from takers. Its revisions
+/// are kept, so stored submissions can still be read against the revision
+/// they were validated with.
+async fn delete(id: &str, stores: &Stores) -> Result<ApiResponse, Error> {
+    if stores.question_lists.get(id).await?.is_none() {
+        return Ok(ApiResponse::not_found());
+    }
+    stores.question_lists.delete(id).await?;
+    Ok(ApiResponse::with_body(
+        200,
+        json!({
+            "id": id,
+        }),
+    ))
+}
+
+/// Maps question ids to the property names their answers are stored under,
+/// for analytics on exported submissions.
+async fn keys(id: &str, stores: &Stores) -> Result<ApiResponse, Error> {
+    match stores.question_lists.get(id).await? {
+        Some(question_list) => Ok(ApiResponse::with_body(
+            200,
+            json!({
+                "id": id,
+                "version": question_list.version,
+                "keys": question_list.key_mappings(),
+            }),
+        )),
+        None => Ok(ApiResponse::not_found()),
+    }
+}
+
+/// The versions of questionnaire `id`, oldest first, without their questions.
+async fn revisions(id: &str, stores: &Stores) -> Result<ApiResponse, Error> {
+    let revisions = stores.revisions.list(id).await?;
+    if revisions.is_empty() && stores.question_lists.get(id).await?.is_none() {
+        return Ok(ApiResponse::not_found());
+    }
+    let revisions: Vec<_> = revisions
+        .iter()
+        .map(|revision| {
+            json!({
+                "version": revision.version,
+                "status": revision.status,
+                "savedAt": revision.saved_at,
+                "publishedAt": revision.published_at,
+            })
+        })
+        .collect();
+    Ok(ApiResponse::with_body(
+        200,
+        json!({
+            "id": id,
+            "revisions": revisions,
+        }),
+    ))
+}
+
+async fn revision(id: &str, version: u32, stores: &Stores) -> Result<ApiResponse, Error> {
+    match stores.revisions.get(id, version).await? {
+        Some(revision) => Ok(ApiResponse::with_body(200, to_value(revision)?)),
+        None => Ok(ApiResponse::not_found()),
+    }
+}
+
+/// Publishes a draft, making it the revision takers see. Publishing is
+/// final, and only moves forwards: a draft older than the latest published
+/// revision cannot be published any more.
+async fn publish(id: &str, version: u32, stores: &Stores) -> Result<ApiResponse, Error> {
+    let revisions = stores.revisions.list(id).await?;
+    let revision = match revisions
+        .iter()
+        .find(|revision| revision.version == version)
+    {
+        Some(revision) => revision,
+        None => return Ok(ApiResponse::not_found()),
+    };
+    if !revision.is_published() {
+        let latest = revisions
+            .iter()
+            .rev()
+            .find(|revision| revision.is_published());
+        if let Some(latest) = latest.filter(|latest| latest.version > version) {
+            return Ok(ApiResponse::problem(
+                409,
+                "superseded",
+                "A later revision is published",
+                format!(
+                    "revision {} is published, so revision {} cannot be",
+                    latest.version, version
+                ),
+            ));
+        }
+        stores.revisions.publish(id, version, Utc::now()).await?;
+    }
+    Ok(ApiResponse::with_body(
+        200,
+        json!({
+            "id": id,
+            "version": version,
+            "status": "published",
+        }),
+    ))
+}
+
+/// The latest published revision of questionnaire `id`.
+async fn published(id: &str, stores: &Stores) -> Result<ApiResponse, Error> {
+    let revisions = stores.revisions.list(id).await?;
+    match revisions.into_iter().rev().find(Revision::is_published) {
+        Some(revision) => Ok(ApiResponse::with_body(200, to_value(revision)?)),
+        None => Ok(ApiResponse::not_found()),
+    }
+}
+
+/// Moves the submissions to revision `from` of a questionnaire over to
+/// revision `to`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct Migration {
+    from: u32,
+    to: u32,
+    #[serde(default)]
+    rules: Vec<Rule>,
+    /// Only report what would happen; on unless turned off.
+    #[serde(default = "dry_run")]
+    dry_run: bool,
+}
+
+fn dry_run() -> bool {
+    true
+}
+
+/// Migrates the submissions to one revision of questionnaire `id` to a later
+/// one, reporting the outcome for each. Nothing is rewritten on a dry run,
+/// nor unless every submission can be migrated. The store cannot rewrite
+/// them all at once, so should a write fail, the submissions already
+/// rewritten are put back and the problem lists them.
+async fn migrate(id: &str, req: &Request, stores: &Stores) -> Result<ApiResponse, Error> {
+    let migration: Migration = match serde_json::from_slice(req.body().as_ref()) {
+        Ok(migration) => migration,
+        Err(err) => {
+            return Ok(ApiResponse::with_body(
+                400,
+                json!({
+                    "message": "invalid migration",
+                    "errors": [err.to_string()],
+                }),
+            ))
+        }
+    };
+    if migration.to <= migration.from {
+        return Ok(ApiResponse::with_body(
+            400,
+            json!({
+                "message": "invalid migration",
+                "errors": ["revision to must be later than revision from"],
+            }),
+        ));
+    }
+    let (from, to) = match (
+        stores.revisions.get(id, migration.from).await?,
+        stores.revisions.get(id, migration.to).await?,
+    ) {
+        (Some(from), Some(to)) => (from, to),
+        _ => return Ok(ApiResponse::not_found()),
+    };
+    if !migration.dry_run && !to.is_published() {
+        return Ok(ApiResponse::problem(
+            409,
+            "unpublished",
+            "Revision is not published",
+            format!(
+                "submissions can only be migrated to a published revision, and {} is a draft",
+                migration.to
+            ),
+        ));
+    }
+    let plan = match Plan::new(&from.question_list, &to.question_list, migration.rules) {
+        Ok(plan) => plan,
+        Err(errors) => {
+            return Ok(ApiResponse::with_body(
+                400,
+                json!({
+                    "message": "invalid rules",
+                    "errors": errors,
+                }),
+            ))
+        }
+    };
+
+    let mut report = vec![];
+    let mut migrated = vec![];
+    for submission in stores.submissions.list(id).await? {
+        if submission.version != migration.from {
+            continue;
+        }
+        match plan.migrate(&submission.answers, submission.submitted_at) {
+            Ok(answers) => {
+                report.push(json!({ "id": submission.id, "status": "migrated" }));
+                let rewritten = Submission {
+                    version: migration.to,
+                    answers,
+                    ..submission.clone()
+                };
+                migrated.push((submission, rewritten));
+            }
+            Err(failure) => report.push(json!({
+                "id": submission.id,
+                "status": "failed",
+                "failure": failure,
+            })),
+        }
+    }
+    let failed = report.len() - migrated.len();
+    let applied = !migration.dry_run && failed == 0;
+    if applied {
+        if let Err(response) = rewrite(stores, &migrated).await {
+            return Ok(response);
+        }
+    }
+    Ok(ApiResponse::with_body(
+        if migration.dry_run || applied {
+            200
+        } else {
+            422
+        },
+        json!({
+            "id": id,
+            "from": migration.from,
+            "to": migration.to,
+            "dryRun": migration.dry_run,
+            "applied": applied,
+            "changes": migration::diff(&from.question_list, &to.question_list),
+            "submissions": report,
+        }),
+    ))
+}
+
+/// Stores each migrated submission in place of its original. If a write
+/// fails, the originals of those already written are stored again; the
+/// problem for the failure lists them under `restored`, and under `migrated`
+/// any that could not be put back either.
+async fn rewrite(
+    stores: &Stores,
+    migrated: &[(Submission, Submission)],
+) -> Result<(), ApiResponse> {
+    for (written, (_, submission)) in migrated.iter().enumerate() {
+        let err = match stores.submissions.put(submission).await {
+            Ok(()) => continue,
+            Err(err) => err,
+        };
+        let (mut restored, mut stranded) = (vec![], vec![]);
+        for (original, _) in &migrated[..written] {
+            match stores.submissions.put(original).await {
+                Ok(()) => restored.push(original.id),
+                Err(_) => stranded.push(original.id),
+            }
+        }
+        let mut response = respond(Err(Error::Store(err)));
+        response.body["restored"] = json!(restored);
+        response.body["migrated"] = json!(stranded);
+        return Err(response);
+    }
+    Ok(())
+}
+
+/// Creates a questionnaire from a JSON Schema document, reporting the
+/// keywords that could not be carried over.
+async fn import(req: &Request, stores: &Stores) -> Result<ApiResponse, Error> {
+    let schema = match serde_json::from_slice(req.body().as_ref()) {
+        Ok(schema) => schema,
+        Err(_) => return Ok(ApiResponse::error()),
+    };
+    let mut import = match schemaimport::import(&schema) {
+        Ok(import) => import,
+        Err(err) => {
+            return Ok(ApiResponse::with_body(
+                400,
+                json!({
+                    "message": "invalid schema",
+                    "errors": [err.to_string()],
+                }),
+            ))
+        }
+    };
+    let diagnostics = match lint_for_save(&import.question_list) {
+        Ok(diagnostics) => diagnostics,
+        Err(response) => return Ok(response),
+    };
+    let id = Uuid::new_v4().to_string();
+    import.question_list.version = 1;
+    save(&id, &import.question_list, stores).await?;
+    Ok(ApiResponse::with_body(
+        201,
+        json!({
+            "id": id,
+            "version": import.question_list.version,
+            "status": "draft",
+            "warnings": import.warnings,
+            "diagnostics": diagnostics,
+        }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{request, taker};
+    use super::*;
+    use crate::qna::questionlist;
+    use crate::store::memory::MemorySubmissionStore;
+    use crate::store::{StoreError, SubmissionStore};
+    use async_trait::async_trait;
+    use lambda_http::Body;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn create_update_read_delete() {
+        let stores = Stores::memory(vec![]);
+        let mut sample = questionlist::sample_string_questions();
+
+        let created = router(
+            request(
+                "POST",
+                "/admin/questionnaires",
+                Body::from(serde_json::to_string(&sample).unwrap()),
+            ),
+            &stores,
+        )
+        .await
+        .unwrap();
+        assert_eq!(created.status, 201);
+        let id = created.body["id"].as_str().unwrap().to_string();
+        let uri = format!("/admin/questionnaires/{}", id);
+
+        sample.title = "Renamed".into();
+        let updated = router(
+            request(
+                "PUT",
+                &uri,
+                Body::from(serde_json::to_string(&sample).unwrap()),
+            ),
+            &stores,
+        )
+        .await
+        .unwrap();
+        assert_eq!(updated.status, 200);
+        assert_eq!(updated.body["version"], 2);
+
+        let read = router(request("GET", &uri, Body::Empty), &stores)
+            .await
+            .unwrap();
+        assert_eq!(read.body["title"], "Renamed");
+
+        let listed = router(
+            request("GET", "/admin/questionnaires", Body::Empty),
+            &stores,
+        )
+        .await
+        .unwrap();
+        assert_eq!(listed.body["questionnaires"], json!([id]));
+
+        let deleted = router(request("DELETE", &uri, Body::Empty), &stores)
+            .await
+            .unwrap();
+        assert_eq!(deleted.status, 200);
+        let read = router(request("GET", &uri, Body::Empty), &stores)
+            .await
+            .unwrap();
+        assert_eq!(read.status, 404);
+    }
+
+    #[tokio::test]
+    async fn takers_see_published_revisions_only() {
+        let stores = Stores::memory(vec![]);
+        let mut sample = questionlist::sample_string_questions();
+        let send = |method: &str, uri: &str, body: Body| {
+            let (req, stores) = (request(method, uri, body), &stores);
+            async move {
+                if req.uri().path().starts_with("/taker") {
+                    taker::router(req, stores).await.unwrap()
+                } else {
+                    router(req, stores).await.unwrap()
+                }
+            }
+        };
+        let save = |method, uri: &str, sample: &QuestionList| {
+            send(
+                method,
+                uri,
+                Body::from(serde_json::to_string(sample).unwrap()),
+            )
+        };
+
+        let created = save("POST", "/admin/questionnaires", &sample).await;
+        assert_eq!(created.body["status"], "draft");
+        let id = created.body["id"].as_str().unwrap().to_string();
+        let admin = format!("/admin/questionnaires/{}", id);
+        let taker = format!("/taker/{}", id);
+        assert_eq!(send("GET", &taker, Body::Empty).await.status, 404);
+
+        let published = send(
+            "POST",
+            &format!("{}/revisions/1/publish", admin),
+            Body::Empty,
+        );
+        assert_eq!(published.await.status, 200);
+        sample.title = "Renamed".into();
+        assert_eq!(save("PUT", &admin, &sample).await.body["version"], 2);
+        let served = send("GET", &taker, Body::Empty).await;
+        assert_eq!(served.body["schema"]["title"], "Zork");
+
+        let answers = json!({
+            "1": "Ice",
+            "8": ["Ice", "Cream"],
+            "88": 10,
+            "222": true,
+            "24": "zork",
+            "3": "Some text",
+            "899": true,
+        });
+        let submitted = send("POST", &taker, Body::from(answers.to_string())).await;
+        let submission_id = submitted.body["id"].as_str().unwrap().parse().unwrap();
+        let submission = stores.submissions.get(&id, submission_id).await.unwrap();
+        assert_eq!(submission.unwrap().version, 1);
+
+        let revisions = send("GET", &format!("{}/revisions", admin), Body::Empty).await;
+        assert_eq!(revisions.body["revisions"][0]["status"], "published");
+        assert_eq!(revisions.body["revisions"][1]["status"], "draft");
+        let revision = send("GET", &format!("{}/revisions/2", admin), Body::Empty).await;
+        assert_eq!(revision.body["question_list"]["title"], "Renamed");
+        let latest = send("GET", &format!("{}/published", admin), Body::Empty).await;
+        assert_eq!(latest.body["version"], 1);
+
+        save("PUT", &admin, &sample).await;
+        let published = send(
+            "POST",
+            &format!("{}/revisions/3/publish", admin),
+            Body::Empty,
+        );
+        assert_eq!(published.await.status, 200);
+        let served = send("GET", &taker, Body::Empty).await;
+        assert_eq!(served.body["schema"]["title"], "Renamed");
+        let pinned = send("GET", &format!("{}/revisions/1", taker), Body::Empty).await;
+        assert_eq!(pinned.body["schema"]["title"], "Zork");
+        let draft = send("GET", &format!("{}/revisions/2", taker), Body::Empty).await;
+        assert_eq!(draft.status, 404);
+
+        let superseded = send(
+            "POST",
+            &format!("{}/revisions/2/publish", admin),
+            Body::Empty,
+        );
+        let superseded = superseded.await;
+        assert_eq!(superseded.status, 409);
+        assert_eq!(superseded.body["type"], "urn:gears:problem:superseded");
+    }
+
+    #[tokio::test]
+    async fn editing_keeps_questionnaires_from_before_revisions_live() {
+        let stores = Stores::memory(vec![(
+            "sample".to_string(),
+            questionlist::sample_string_questions(),
+        )]);
+        let mut sample = questionlist::sample_string_questions();
+        sample.title = "Renamed".into();
+
+        let updated = router(
+            request(
+                "PUT",
+                "/admin/questionnaires/sample",
+                Body::from(serde_json::to_string(&sample).unwrap()),
+            ),
+            &stores,
+        )
+        .await
+        .unwrap();
+        assert_eq!(updated.body["version"], 2);
+
+        let served = taker::router(request("GET", "/taker/sample", Body::Empty), &stores)
+            .await
+            .unwrap();
+        assert_eq!(served.body["schema"]["title"], "Zork");
+        let revisions = stores.revisions.list("sample").await.unwrap();
+        assert!(revisions[0].is_published());
+        assert!(!revisions[1].is_published());
+    }
+
+    #[tokio::test]
+    async fn saves_after_a_revision_the_current_state_missed() {
+        let sample = questionlist::sample_string_questions();
+        let stores = Stores::memory(vec![("sample".to_string(), sample.clone())]);
+        // A save that created revision 2 but failed to write the current state.
+        for version in 1..=2 {
+            let mut question_list = sample.clone();
+            question_list.version = version;
+            stores
+                .revisions
+                .create(&Revision::draft("sample", question_list))
+                .await
+                .unwrap();
+        }
+
+        let updated = router(
+            request(
+                "PUT",
+                "/admin/questionnaires/sample",
+                Body::from(serde_json::to_string(&sample).unwrap()),
+            ),
+            &stores,
+        )
+        .await
+        .unwrap();
+        assert_eq!(updated.status, 200);
+        assert_eq!(updated.body["version"], 3);
+        let current = stores.question_lists.get("sample").await.unwrap().unwrap();
+        assert_eq!(current.version, 3);
+    }
+
+    /// Publishes the sample as revision 1, and as revision 2 with the `bork`
+    /// item renamed.
+    async fn publish_revisions(stores: &Stores) {
+        let sample = questionlist::sample_string_questions();
+        let mut revised = sample.clone();
+        revised.version = 2;
+        if let questionlist::Question::FixedList(list) = &mut revised.questions[4] {
+            list.items = vec!["zork".into(), "borked".into()];
+        }
+        for question_list in [sample, revised] {
+            let version = question_list.version;
+            stores
+                .revisions
+                .create(&Revision::draft("sample", question_list))
+                .await
+                .unwrap();
+            stores
+                .revisions
+                .publish("sample", version, Utc::now())
+                .await
+                .unwrap();
+        }
+    }
+
+    fn sample_answers() -> serde_json::Value {
+        json!({
+            "1": "Ice",
+            "8": ["Ice"],
+            "88": 10,
+            "222": false,
+            "24": "bork",
+            "899": true,
+        })
+    }
+
+    #[tokio::test]
+    async fn migrates_submissions_between_revisions() {
+        let stores = Stores::memory(vec![]);
+        publish_revisions(&stores).await;
+        let answers = sample_answers();
+        let submission = Submission::new("sample", 1, answers.clone());
+        stores.submissions.put(&submission).await.unwrap();
+        let migrate = |body: serde_json::Value| {
+            router(
+                request(
+                    "POST",
+                    "/admin/questionnaires/sample/migrations",
+                    Body::from(body.to_string()),
+                ),
+                &stores,
+            )
+        };
+        let stored = || async {
+            stores
+                .submissions
+                .get("sample", submission.id)
+                .await
+                .unwrap()
+                .unwrap()
+        };
+
+        let report = migrate(json!({ "from": 1, "to": 2 })).await.unwrap();
+        assert_eq!(report.status, 200);
+        assert_eq!(report.body["changes"][0]["change"], "items-removed");
+        assert_eq!(report.body["submissions"][0]["status"], "failed");
+        assert_eq!(
+            report.body["submissions"][0]["failure"]["reason"],
+            "invalid"
+        );
+
+        let rules = json!([
+            { "rule": "rename-item", "questionId": 24, "from": "bork", "to": "borked" },
+        ]);
+        let report = migrate(json!({ "from": 1, "to": 2, "rules": rules }))
+            .await
+            .unwrap();
+        assert_eq!(report.body["submissions"][0]["status"], "migrated");
+        assert_eq!(report.body["applied"], false);
+        assert_eq!(stored().await.answers, answers);
+
+        let report = migrate(json!({ "from": 1, "to": 2, "dryRun": false }))
+            .await
+            .unwrap();
+        assert_eq!(report.status, 422);
+        assert_eq!(stored().await.version, 1);
+
+        let report = migrate(json!({ "from": 1, "to": 2, "rules": rules, "dryRun": false }))
+            .await
+            .unwrap();
+        assert_eq!(report.body["applied"], true);
+        let stored = stored().await;
+        assert_eq!(stored.version, 2);
+        assert_eq!(stored.answers["24"], "borked");
+
+        let bad_rule = json!([{ "rule": "drop-question", "questionId": 404 }]);
+        let report = migrate(json!({ "from": 1, "to": 2, "rules": bad_rule }))
+            .await
+            .unwrap();
+        assert_eq!(report.status, 400);
+        let missing = migrate(json!({ "from": 1, "to": 3 })).await.unwrap();
+        assert_eq!(missing.status, 404);
+    }
+
+    /// Submissions kept in memory, where the write numbered `fail_at`
+    /// counting from zero fails.
+    struct FailingSubmissionStore {
+        inner: MemorySubmissionStore,
+        puts: AtomicUsize,
+        fail_at: usize,
+    }
+
+    #[async_trait]
+    impl SubmissionStore for FailingSubmissionStore {
+        async fn put(&self, submission: &Submission) -> Result<(), StoreError> {
+            if self.puts.fetch_add(1, Ordering::SeqCst) == self.fail_at {
+                return Err(StoreError::Backend("throttled".into()));
+            }
+            self.inner.put(submission).await
+        }
+
+        async fn get(
+            &self,
+            questionnaire_id: &str,
+            id: Uuid,
+        ) -> Result<Option<Submission>, StoreError> {
+            self.inner.get(questionnaire_id, id).await
+        }
+
+        async fn list(&self, questionnaire_id: &str) -> Result<Vec<Submission>, StoreError> {
+            self.inner.list(questionnaire_id).await
+        }
+    }
+
+    #[tokio::test]
+    async fn puts_submissions_back_when_a_migration_fails() {
+        let inner = MemorySubmissionStore::new();
+        let first = Submission::new("sample", 1, sample_answers());
+        let second = Submission {
+            id: Uuid::new_v4(),
+            submitted_at: first.submitted_at + chrono::Duration::seconds(1),
+            ..first.clone()
+        };
+        let submissions = vec![first, second];
+        for submission in &submissions {
+            inner.put(submission).await.unwrap();
+        }
+        let stores = Stores {
+            submissions: Arc::new(FailingSubmissionStore {
+                inner,
+                puts: AtomicUsize::new(0),
+                fail_at: 1,
+            }),
+            ..Stores::memory(vec![])
+        };
+        publish_revisions(&stores).await;
+
+        let rules = json!([
+            { "rule": "rename-item", "questionId": 24, "from": "bork", "to": "borked" },
+        ]);
+        let body = json!({ "from": 1, "to": 2, "rules": rules, "dryRun": false });
+        let response = router(
+            request(
+                "POST",
+                "/admin/questionnaires/sample/migrations",
+                Body::from(body.to_string()),
+            ),
+            &stores,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status, 503);
+        assert_eq!(response.body["restored"], json!([submissions[0].id]));
+        assert_eq!(response.body["migrated"], json!([]));
+        for submission in &submissions {
+            let stored = stores
+                .submissions
+                .get("sample", submission.id)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(&stored, submission);
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_questionnaire_with_lint_errors() {
+        let stores = Stores::memory(vec![]);
+        let mut sample = questionlist::sample_string_questions();
+        if let questionlist::Question::Integer(q) = &mut sample.questions[2] {
+            q.default = Some(7);
+        }
+
+        let response = router(
+            request(
+                "POST",
+                "/admin/questionnaires",
+                Body::from(serde_json::to_string(&sample).unwrap()),
+            ),
+            &stores,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status, 422);
+        assert_eq!(
+            response.body["diagnostics"][0]["code"],
+            "default-unreachable"
+        );
+        assert!(stores.question_lists.list().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn maps_question_ids_to_keys() {
+        let stores = Stores::memory(vec![(
+            "sample".to_string(),
+            questionlist::sample_string_questions(),
+        )]);
+
+        let response = router(
+            request("GET", "/admin/questionnaires/sample/keys", Body::Empty),
+            &stores,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status, 200);
+        let keys = response.body["keys"].as_array().unwrap();
+        assert_eq!(
+            keys[0],
+            json!({ "questionId": 1, "property": "1", "parentId": null })
+        );
+        assert!(
+            keys.contains(&json!({ "questionId": 30, "property": "dependants", "parentId": null }))
+        );
+        assert!(keys.contains(&json!({ "questionId": 33, "property": "age", "parentId": 31 })));
+
+        let response = router(
+            request("GET", "/admin/questionnaires/missing/keys", Body::Empty),
+            &stores,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status, 404);
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_fields() {
+        let stores = Stores::memory(vec![]);
+        let mut document = serde_json::to_value(questionlist::sample_string_questions()).unwrap();
+        document["colour"] = json!("blue");
+
+        let response = router(
+            request(
+                "POST",
+                "/admin/questionnaires",
+                Body::from(document.to_string()),
+            ),
+            &stores,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status, 400);
+        assert!(stores.question_lists.list().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,191 @@
+//! Cross-origin resource sharing: answers preflight requests and lets the
+//! configured origins read responses.
+
+use std::env;
+
+use lambda_http::http::header::{self, HeaderValue};
+use lambda_http::http::{Method, StatusCode};
+use lambda_http::{Body, Request, Response};
+
+const METHODS: &str = "GET, POST, PUT, DELETE, OPTIONS";
+const HEADERS: &str = "Content-Type, Authorization, X-Api-Key";
+/// How long, in seconds, a browser may cache a preflight answer.
+const MAX_AGE: u32 = 600;
+
+/// Which origins may call the API, and what a preflight answer allows them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cors {
+    /// Origins echoed back along with `Access-Control-Allow-Credentials`; an
+    /// entry of `*` lets any origin in, but without credentials.
+    origins: Vec<String>,
+    max_age: u32,
+}
+
+impl Cors {
+    pub fn new<I: IntoIterator<Item = S>, S: Into<String>>(origins: I) -> Self {
+        Self {
+            origins: origins.into_iter().map(Into::into).collect(),
+            max_age: MAX_AGE,
+        }
+    }
+
+    /// Origins from the comma-separated `CORS_ALLOWED_ORIGINS`, none when it
+    /// is unset, and the preflight max-age from `CORS_MAX_AGE`.
+    pub fn from_env() -> Self {
+        let origins = env::var("CORS_ALLOWED_ORIGINS").unwrap_or_default();
+        let mut cors = Self::new(
+            origins
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty()),
+        );
+        if let Some(max_age) = env::var("CORS_MAX_AGE")
+            .ok()
+            .and_then(|max_age| max_age.parse().ok())
+        {
+            cors.max_age = max_age;
+        }
+        cors
+    }
+
+    /// The `Access-Control-Allow-Origin` value for a request from `origin`,
+    /// if it may read responses.
+    fn allowed_origin(&self, origin: Option<&HeaderValue>) -> Option<HeaderValue> {
+        let origin = origin?;
+        if self.origins.iter().any(|allowed| allowed == "*") {
+            Some(HeaderValue::from_static("*"))
+        } else if self.origins.iter().any(|allowed| allowed == origin) {
+            Some(origin.clone())
+        } else {
+            None
+        }
+    }
+
+    /// The answer to `req` when it is a preflight request. Origins that are
+    /// not allowed get one without any CORS headers, which the browser
+    /// treats as a refusal.
+    pub fn preflight(&self, req: &Request) -> Option<Response<Body>> {
+        if req.method() != Method::OPTIONS
+            || !req
+                .headers()
+                .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD)
+        {
+            return None;
+        }
+        let mut response = Response::new(Body::Empty);
+        *response.status_mut() = StatusCode::NO_CONTENT;
+        self.apply(req.headers().get(header::ORIGIN), &mut response);
+        if response
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        {
+            let headers = response.headers_mut();
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_METHODS,
+                HeaderValue::from_static(METHODS),
+            );
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_HEADERS,
+                HeaderValue::from_static(HEADERS),
+            );
+            headers.insert(header::ACCESS_CONTROL_MAX_AGE, self.max_age.into());
+        }
+        Some(response)
+    }
+
+    /// Adds the headers letting a request from `origin` read `response`.
+    pub fn apply(&self, origin: Option<&HeaderValue>, response: &mut Response<Body>) {
+        let headers = response.headers_mut();
+        headers.append(header::VARY, HeaderValue::from_static("Origin"));
+        if let Some(origin) = self.allowed_origin(origin) {
+            if origin != "*" {
+                headers.insert(
+                    header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                    HeaderValue::from_static("true"),
+                );
+            }
+            headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::request;
+    use super::*;
+
+    fn from(origin: &str, method: &str, preflight: bool) -> Request {
+        let mut req = request(method, "/taker/sample", Body::Empty);
+        let headers = req.headers_mut();
+        headers.insert(header::ORIGIN, origin.parse().unwrap());
+        if preflight {
+            headers.insert(
+                header::ACCESS_CONTROL_REQUEST_METHOD,
+                HeaderValue::from_static("POST"),
+            );
+        }
+        req
+    }
+
+    #[test]
+    fn answers_preflight_for_allowed_origins() {
+        let cors = Cors::new(vec!["https://forms.example.com"]);
+
+        let response = cors
+            .preflight(&from("https://forms.example.com", "OPTIONS", true))
+            .expect("is a preflight request");
+        assert_eq!(response.status(), 204);
+        let headers = response.headers();
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://forms.example.com"
+        );
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_METHODS], METHODS);
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_HEADERS], HEADERS);
+        assert_eq!(headers[header::ACCESS_CONTROL_MAX_AGE], "600");
+
+        let response = cors
+            .preflight(&from("https://evil.example.com", "OPTIONS", true))
+            .expect("is a preflight request");
+        assert_eq!(response.status(), 204);
+        assert!(!response
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+
+        assert!(cors
+            .preflight(&from("https://forms.example.com", "OPTIONS", false))
+            .is_none());
+        assert!(cors
+            .preflight(&from("https://forms.example.com", "GET", false))
+            .is_none());
+    }
+
+    #[test]
+    fn echoes_only_allowed_origins() {
+        let cors = Cors::new(vec!["https://forms.example.com"]);
+        let apply = |cors: &Cors, origin| {
+            let mut response = Response::new(Body::Empty);
+            cors.apply(Some(&HeaderValue::from_static(origin)), &mut response);
+            response
+        };
+
+        let response = apply(&cors, "https://forms.example.com");
+        assert_eq!(
+            response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://forms.example.com"
+        );
+        assert_eq!(response.headers()[header::VARY], "Origin");
+
+        let response = apply(&cors, "https://evil.example.com");
+        assert!(!response
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+
+        let response = apply(&Cors::new(vec!["*"]), "https://evil.example.com");
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+        assert!(!response
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_CREDENTIALS));
+    }
+}
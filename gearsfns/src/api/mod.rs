@@ -0,0 +1,286 @@
+use std::future::Future;
+
+use lambda_http::http::{header, StatusCode};
+use lambda_http::request::RequestContext;
+use lambda_http::{handler, lambda, Body, IntoResponse, Request, RequestExt, Response};
+use serde_json::{json, Value};
+
+use crate::qna::lint::Diagnostic;
+use crate::qna::validation::ValidationError;
+use crate::store::{StoreError, Stores};
+use crate::Error;
+
+pub mod admin;
+pub mod cors;
+pub mod taker;
+
+const JSON: &str = "application/json";
+const PROBLEM_JSON: &str = "application/problem+json";
+
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: Value,
+    pub content_type: &'static str,
+}
+
+impl ApiResponse {
+    pub fn ok() -> Self {
+        Self::default()
+    }
+    pub fn with_body(status: u16, body: Value) -> Self {
+        Self {
+            status,
+            body,
+            content_type: JSON,
+        }
+    }
+    pub fn error() -> Self {
+        Self::with_body(
+            400,
+            json!({
+                "message": "bad input",
+            }),
+        )
+    }
+    pub fn not_found() -> Self {
+        Self::with_body(
+            404,
+            json!({
+                "message": "questionnaire not found",
+            }),
+        )
+    }
+    pub fn method_not_allowed() -> Self {
+        Self::with_body(405, json!({}))
+    }
+    /// An RFC 7807 problem listing every answer that failed validation.
+    pub fn invalid_answers(errors: &[ValidationError]) -> Self {
+        Self {
+            status: 400,
+            body: json!({
+                "type": "urn:gears:problem:invalid-answers",
+                "title": "Answers do not validate",
+                "status": 400,
+                "detail": format!("{} answer(s) failed validation", errors.len()),
+                "errors": errors,
+            }),
+            content_type: PROBLEM_JSON,
+        }
+    }
+    /// An RFC 7807 problem listing the linter diagnostics of a questionnaire.
+    pub fn invalid_questionnaire(status: u16, diagnostics: &[Diagnostic]) -> Self {
+        Self {
+            status,
+            body: json!({
+                "type": "urn:gears:problem:invalid-questionnaire",
+                "title": "Questionnaire has authoring errors",
+                "status": status,
+                "detail": format!("{} problem(s) found", diagnostics.len()),
+                "diagnostics": diagnostics,
+            }),
+            content_type: PROBLEM_JSON,
+        }
+    }
+    /// An RFC 7807 problem for a request that could not be served.
+    fn problem(status: u16, kind: &str, title: &str, detail: String) -> Self {
+        Self {
+            status,
+            body: json!({
+                "type": format!("urn:gears:problem:{}", kind),
+                "title": title,
+                "status": status,
+                "detail": detail,
+            }),
+            content_type: PROBLEM_JSON,
+        }
+    }
+}
+
+impl From<Error> for ApiResponse {
+    fn from(err: Error) -> Self {
+        let detail = err.to_string();
+        match err {
+            Error::Conversion(_) => {
+                Self::problem(500, "conversion", "Questionnaire cannot be served", detail)
+            }
+            Error::Schema(_) => Self::problem(
+                500,
+                "schema",
+                "Questionnaire schema does not compile",
+                detail,
+            ),
+            Error::Store(StoreError::Backend(_)) => {
+                Self::problem(503, "storage", "Storage is unavailable", detail)
+            }
+            Error::Store(StoreError::Conflict(_)) => {
+                Self::problem(409, "conflict", "Revision was changed concurrently", detail)
+            }
+            Error::Store(StoreError::Serialization(_)) => {
+                Self::problem(500, "storage", "Stored document is invalid", detail)
+            }
+            Error::Validation(errors) => Self::invalid_answers(&errors),
+        }
+    }
+}
+
+/// The response for a router's result, turning any error into the problem
+/// it maps to. Server-side failures are logged.
+pub fn respond(result: Result<ApiResponse, Error>) -> ApiResponse {
+    result.unwrap_or_else(|err| {
+        let response = ApiResponse::from(err);
+        if response.status >= 500 {
+            log::error!("Request failed: {}", response.body["detail"]);
+        }
+        response
+    })
+}
+
+/// Serves `req` with `router`, answering CORS preflight requests before they
+/// reach it and adding CORS headers to every response.
+pub async fn handle<R, F>(req: Request, cors: &cors::Cors, router: R) -> Response<Body>
+where
+    R: FnOnce(Request) -> F,
+    F: Future<Output = Result<ApiResponse, Error>>,
+{
+    if let Some(response) = cors.preflight(&req) {
+        return response;
+    }
+    let origin = req.headers().get(header::ORIGIN).cloned();
+    let mut response = respond(router(req).await).into_response();
+    cors.apply(origin.as_ref(), &mut response);
+    response
+}
+
+/// The error type the Lambda runtime expects. Requests themselves never fail
+/// since every `Error` maps to a problem response; only starting up can.
+pub type LambdaError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// Runs a Lambda function serving `router`, with the stores and CORS policy
+/// the environment configures.
+pub async fn run<R, F>(router: R) -> Result<(), LambdaError>
+where
+    R: Fn(Request, Stores) -> F + Clone + Send + Sync + 'static,
+    F: Future<Output = Result<ApiResponse, Error>> + Send + 'static,
+{
+    simple_logger::init_with_level(log::Level::Info)?;
+    let stores = Stores::from_env()?;
+    let cors = cors::Cors::from_env();
+    lambda::run(handler(move |req, _| {
+        let (router, stores, cors) = (router.clone(), stores.clone(), cors.clone());
+        async move {
+            let response = handle(req, &cors, |req| router(req, stores)).await;
+            Ok::<_, LambdaError>(response)
+        }
+    }))
+    .await?;
+    Ok(())
+}
+
+impl Default for ApiResponse {
+    fn default() -> Self {
+        Self::with_body(200, json!({}))
+    }
+}
+
+impl IntoResponse for ApiResponse {
+    fn into_response(self) -> Response<Body> {
+        Response::builder()
+            .status(self.status)
+            .header("Content-Type", self.content_type)
+            .body(Body::Text(self.body.to_string()))
+            .unwrap_or_else(|_| {
+                let mut response = Response::new(Body::Empty);
+                *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                response
+            })
+    }
+}
+
+/// The non-empty segments of the request path. An HTTP API puts the stage
+/// in front of the route unless it is the `$default` stage, so that is
+/// dropped to route REST API, HTTP API and load balancer events alike.
+fn path_segments(req: &Request) -> Vec<&str> {
+    let mut segments: Vec<&str> = req
+        .uri()
+        .path()
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+    if let Some(RequestContext::ApiGatewayV2(context)) = req.extensions().get() {
+        if segments.first() == Some(&context.stage.as_str()) {
+            segments.remove(0);
+        }
+    }
+    segments
+}
+
+/// A query string parameter, taken from the event when the integration
+/// parsed it and from the request URI otherwise.
+fn query_parameter(req: &Request, name: &str) -> Option<String> {
+    if let Some(value) = req.query_string_parameters().get(name) {
+        return Some(value.to_string());
+    }
+    req.uri().query().and_then(|query| {
+        query.split('&').find_map(|pair| {
+            let mut parts = pair.splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) if key == name => Some(value.to_string()),
+                _ => None,
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+pub(crate) fn request(method: &str, uri: &str, body: Body) -> Request {
+    lambda_http::http::Request::builder()
+        .method(method)
+        .uri(uri)
+        .body(body)
+        .expect("valid request")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_map_to_problems() {
+        let cases = vec![
+            (
+                Error::Conversion("bad".into()),
+                500,
+                "urn:gears:problem:conversion",
+            ),
+            (Error::Schema("bad".into()), 500, "urn:gears:problem:schema"),
+            (
+                Error::Store(StoreError::Backend("timeout".into())),
+                503,
+                "urn:gears:problem:storage",
+            ),
+            (
+                Error::Store(StoreError::Conflict("taken".into())),
+                409,
+                "urn:gears:problem:conflict",
+            ),
+            (
+                Error::Validation(vec![]),
+                400,
+                "urn:gears:problem:invalid-answers",
+            ),
+        ];
+        for (err, status, kind) in cases {
+            let response = respond(Err(err));
+            assert_eq!(response.status, status);
+            assert_eq!(response.content_type, PROBLEM_JSON);
+            assert_eq!(response.body["type"], kind);
+            assert_eq!(response.body["status"], status);
+        }
+    }
+
+    #[test]
+    fn invalid_status_is_a_server_error() {
+        let response = ApiResponse::with_body(1000, json!({})).into_response();
+        assert_eq!(response.status(), 500);
+    }
+}
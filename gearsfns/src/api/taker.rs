@@ -0,0 +1,311 @@
+use std::convert::TryFrom;
+
+use lambda_http::Request;
+use serde_json::{json, to_value, Value};
+
+use super::{path_segments, query_parameter, ApiResponse};
+use crate::qna::{lint, questiondto, questionlist, validation, validator};
+use crate::store::{Revision, Stores, Submission};
+use crate::Error;
+
+/// The questionnaire id from a `/taker/{id}` path, along with the version
+/// from a `/taker/{id}/revisions/{version}` one.
+fn questionnaire_id(req: &Request) -> Option<(&str, Option<u32>)> {
+    match path_segments(req).as_slice() {
+        ["taker", id] => Some((*id, None)),
+        ["taker", id, "revisions", version] => {
+            version.parse().ok().map(|version| (*id, Some(version)))
+        }
+        _ => None,
+    }
+}
+
+/// The revision takers see at `version`, or the latest published one.
+/// Questionnaires saved before revisions were introduced have none, and are
+/// served as stored until a revision of them is published.
+async fn published(
+    id: &str,
+    version: Option<u32>,
+    stores: &Stores,
+) -> Result<Option<questionlist::QuestionList>, Error> {
+    let current = match stores.question_lists.get(id).await? {
+        Some(current) => current,
+        None => return Ok(None),
+    };
+    let revisions = stores.revisions.list(id).await?;
+    if revisions.is_empty() && version.is_none_or(|version| version == current.version) {
+        return Ok(Some(current));
+    }
+    Ok(revisions
+        .into_iter()
+        .rev()
+        .filter(Revision::is_published)
+        .find(|revision| version.is_none_or(|version| version == revision.version))
+        .map(|revision| revision.question_list))
+}
+
+impl ApiResponse {
+    pub fn submitted(submission: &Submission) -> Self {
+        Self::with_body(
+            200,
+            json!({
+                "id": submission.id,
+            }),
+        )
+    }
+}
+
+pub async fn router(req: Request, stores: &Stores) -> Result<ApiResponse, Error> {
+    let (id, version) = match questionnaire_id(&req) {
+        Some((id, version)) => (id.to_string(), version),
+        None => return Ok(ApiResponse::not_found()),
+    };
+    let question_list = match published(&id, version, stores).await? {
+        Some(question_list) => question_list,
+        None => return Ok(ApiResponse::not_found()),
+    };
+    // Questionnaires saved before linting was introduced may still be broken.
+    let diagnostics = lint::lint(&question_list);
+    if lint::has_errors(&diagnostics) {
+        log::warn!("Questionnaire {} has authoring errors", id);
+        return Ok(ApiResponse::invalid_questionnaire(500, &diagnostics));
+    }
+
+    // Answers are only taken against the latest published revision.
+    match req.method().as_str() {
+        "POST" if version.is_none() => post_answer(req, &id, question_list, stores).await,
+        "GET" => get_question(req, question_list),
+        _ => Ok(ApiResponse::method_not_allowed()),
+    }
+}
+
+/// Serves the form bundle, or only the member named by `?part=`.
+fn get_question(
+    req: Request,
+    question_list: questionlist::QuestionList,
+) -> Result<ApiResponse, Error> {
+    let bundle = to_value(questiondto::FormBundle::try_from(question_list)?)?;
+    Ok(match query_parameter(&req, "part") {
+        None => ApiResponse::with_body(200, bundle),
+        Some(part) => match bundle.get(&part) {
+            Some(body) => ApiResponse::with_body(200, body.clone()),
+            None => ApiResponse::with_body(
+                400,
+                json!({
+                    "message": "unknown part",
+                    "parts": ["schema", "uiSchema", "formData"],
+                }),
+            ),
+        },
+    })
+}
+
+async fn post_answer(
+    req: Request,
+    id: &str,
+    question_list: questionlist::QuestionList,
+    stores: &Stores,
+) -> Result<ApiResponse, Error> {
+    if let Ok(val) = serde_json::from_slice::<Value>(req.body().as_ref()) {
+        let compiled = stores.schemas.get(id, &question_list)?;
+
+        let answers = validation::validate_with_schema(&question_list, &compiled, &val)
+            .and_then(|_| validator::check(&question_list, &val));
+        match answers {
+            Err(errors) => {
+                for error in &errors {
+                    log::debug!("Validation error: {} {}", error.pointer, error.message);
+                }
+                Err(Error::Validation(errors))
+            }
+            Ok(answers) => {
+                let answers = answers.to_json(&question_list.questions);
+                let submission = Submission::new(id, question_list.version, answers);
+                stores.submissions.put(&submission).await?;
+                Ok(ApiResponse::submitted(&submission))
+            }
+        }
+    } else {
+        Ok(ApiResponse::error())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{request, respond};
+    use super::*;
+    use lambda_http::{Body, IntoResponse};
+    use serde_json::json;
+
+    fn sample_stores() -> Stores {
+        Stores::memory(vec![(
+            "sample".to_string(),
+            questionlist::sample_string_questions(),
+        )])
+    }
+
+    #[tokio::test]
+    async fn post_answer_handles_empty_request() {
+        let request = request("POST", "/taker/sample", Body::Empty);
+        let expected = ApiResponse::error().into_response();
+        let response = router(request, &sample_stores())
+            .await
+            .expect("expected Ok(_) value")
+            .into_response();
+        assert_eq!(response.status(), 400);
+        assert_eq!(response.body(), expected.body())
+    }
+
+    #[tokio::test]
+    async fn get_question_handles() {
+        let request = request("GET", "/taker/sample", Body::Empty);
+        let expected = json!(questiondto::FormBundle::try_from(
+            questionlist::sample_string_questions()
+        )
+        .unwrap())
+        .into_response();
+        let response = router(request, &sample_stores())
+            .await
+            .expect("expected Ok(_) value")
+            .into_response();
+        assert_eq!(response.body(), expected.body())
+    }
+
+    #[tokio::test]
+    async fn get_question_selects_part() {
+        let stores = sample_stores();
+        let get = |uri: &str| router(request("GET", uri, Body::Empty), &stores);
+
+        let response = get("/taker/sample?part=uiSchema").await.unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(
+            response.body["899"],
+            json!({ "ui:help": "You must agree to continue", "ui:widget": "radio" })
+        );
+
+        let response = get("/taker/sample?part=formData").await.unwrap();
+        assert_eq!(response.body["88"], json!(10));
+
+        let response = get("/taker/sample?part=nope").await.unwrap();
+        assert_eq!(response.status, 400);
+    }
+
+    /// Recorded REST API, HTTP API and load balancer events for the same two
+    /// requests, which must route and answer alike.
+    const EVENTS: &[(&str, &str, &str)] = &[
+        (
+            "apigw_v1",
+            include_str!("fixtures/apigw_v1_get.json"),
+            include_str!("fixtures/apigw_v1_post.json"),
+        ),
+        (
+            "apigw_v2",
+            include_str!("fixtures/apigw_v2_get.json"),
+            include_str!("fixtures/apigw_v2_post.json"),
+        ),
+        (
+            "alb",
+            include_str!("fixtures/alb_get.json"),
+            include_str!("fixtures/alb_post.json"),
+        ),
+    ];
+
+    #[tokio::test]
+    async fn replays_recorded_events() {
+        let stores = sample_stores();
+        let expected = respond(
+            router(
+                request("GET", "/taker/sample?part=schema", Body::Empty),
+                &stores,
+            )
+            .await,
+        )
+        .into_response();
+        for (shape, get, post) in EVENTS {
+            let event = lambda_http::request::from_str(get).unwrap();
+            let response = respond(router(event, &stores).await).into_response();
+            assert_eq!(response.status(), 200, "{}", shape);
+            assert_eq!(response.headers(), expected.headers(), "{}", shape);
+            assert_eq!(response.body(), expected.body(), "{}", shape);
+
+            let event = lambda_http::request::from_str(post).unwrap();
+            let response = respond(router(event, &stores).await);
+            assert_eq!(response.status, 200, "{}", shape);
+            let id = response.body["id"].as_str().unwrap().parse().unwrap();
+            let submission = stores
+                .submissions
+                .get("sample", id)
+                .await
+                .unwrap()
+                .expect("submission is stored");
+            assert_eq!(
+                submission.answers["8"],
+                json!(["Ice", "Cream"]),
+                "{}",
+                shape
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn unknown_questionnaire_is_not_found() {
+        let request = request("GET", "/taker/missing", Body::Empty);
+        let response = router(request, &sample_stores())
+            .await
+            .expect("expected Ok(_) value")
+            .into_response();
+        assert_eq!(response.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn post_answer_stores_valid_submission() {
+        let stores = sample_stores();
+        let answers = json!({
+            "1": "Ice",
+            "8": ["Ice", "Cream"],
+            "88": 10,
+            "222": true,
+            "24": "zork",
+            "3": "Some text",
+            "899": true,
+        });
+        let request = request("POST", "/taker/sample", Body::from(answers.to_string()));
+        let response = router(request, &stores)
+            .await
+            .expect("expected Ok(_) value")
+            .into_response();
+        assert_eq!(response.status(), 200);
+
+        let body: Value = serde_json::from_slice(response.body().as_ref()).unwrap();
+        let id = body["id"].as_str().unwrap().parse().unwrap();
+        let submission = stores
+            .submissions
+            .get("sample", id)
+            .await
+            .unwrap()
+            .expect("submission is stored");
+        assert_eq!(submission.version, 1);
+        assert_eq!(submission.answers, answers);
+        assert_eq!(stores.schemas.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn post_answer_reports_problem() {
+        let answers = json!({ "88": "ten" });
+        let request = request("POST", "/taker/sample", Body::from(answers.to_string()));
+        let response = respond(router(request, &sample_stores()).await);
+        assert_eq!(response.status, 400);
+        assert_eq!(response.content_type, "application/problem+json");
+        assert_eq!(response.body["status"], 400);
+
+        let type_error = response.body["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|e| e["pointer"] == "/88")
+            .expect("error for question 88");
+        assert_eq!(type_error["questionId"], 88);
+        assert_eq!(type_error["title"], "A number");
+        assert_eq!(type_error["code"], "type");
+    }
+}
@@ -0,0 +1,50 @@
+use std::fmt;
+
+use crate::qna::validation::ValidationError;
+use crate::store::StoreError;
+
+/// Everything that can go wrong while serving a request.
+#[derive(Debug)]
+pub enum Error {
+    /// A questionnaire breaks an invariant its schema relies on, or a value
+    /// could not be converted to or from JSON.
+    Conversion(String),
+    /// The schema emitted for a questionnaire did not compile.
+    Schema(String),
+    /// A storage backend failed, or refused a write.
+    Store(StoreError),
+    /// Submitted answers failed validation.
+    Validation(Vec<ValidationError>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Conversion(message) => write!(f, "conversion failed: {}", message),
+            Self::Schema(message) => write!(f, "schema does not compile: {}", message),
+            Self::Store(err) => write!(f, "{}", err),
+            Self::Validation(errors) => write!(f, "{} answer(s) failed validation", errors.len()),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Store(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Conversion(err.to_string())
+    }
+}
+
+impl From<StoreError> for Error {
+    fn from(err: StoreError) -> Self {
+        Self::Store(err)
+    }
+}
@@ -0,0 +1,6 @@
+use gearsfn::api::{self, admin, LambdaError};
+
+#[tokio::main]
+async fn main() -> Result<(), LambdaError> {
+    api::run(|req, stores| async move { admin::router(req, &stores).await }).await
+}
@@ -0,0 +1,90 @@
+//! Serves the taker and admin routes on a local port, reading questionnaires
+//! from a directory of JSON or YAML files and keeping submissions in memory:
+//!
+//!     cargo run --bin gears-dev -- [dir] [port]
+//!
+//! `dir` defaults to `questionnaires` and `port` to 3000. Any origin may call
+//! it unless `CORS_ALLOWED_ORIGINS` says otherwise.
+
+use std::convert::Infallible;
+use std::env;
+use std::net::SocketAddr;
+
+use hyper::service::{make_service_fn, service_fn};
+use lambda_http::{Body, Request};
+
+use gearsfn::api::{self, admin, cors::Cors, taker};
+use gearsfn::store::Stores;
+
+type DevError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// Turns a hyper request into the one API Gateway would have handed the
+/// function; bodies that are not UTF-8 arrive as binary, as they would there.
+async fn to_lambda(req: hyper::Request<hyper::Body>) -> Result<Request, hyper::Error> {
+    let (parts, body) = req.into_parts();
+    let bytes = hyper::body::to_bytes(body).await?;
+    let body = match String::from_utf8(bytes.to_vec()) {
+        Ok(text) if text.is_empty() => Body::Empty,
+        Ok(text) => Body::Text(text),
+        Err(err) => Body::Binary(err.into_bytes()),
+    };
+    Ok(Request::from_parts(parts, body))
+}
+
+fn to_hyper(response: lambda_http::Response<Body>) -> hyper::Response<hyper::Body> {
+    let (parts, body) = response.into_parts();
+    let body = match body {
+        Body::Empty => hyper::Body::empty(),
+        Body::Text(text) => text.into(),
+        Body::Binary(bytes) => bytes.into(),
+    };
+    hyper::Response::from_parts(parts, body)
+}
+
+/// Dispatches on the first path segment, the way API Gateway picks a
+/// function from `serverless.yml`.
+async fn serve(
+    req: hyper::Request<hyper::Body>,
+    stores: Stores,
+    cors: Cors,
+) -> Result<hyper::Response<hyper::Body>, hyper::Error> {
+    let req = to_lambda(req).await?;
+    let (method, path) = (req.method().clone(), req.uri().path().to_string());
+    let response = if path == "/taker" || path.starts_with("/taker/") {
+        api::handle(req, &cors, |req| taker::router(req, &stores)).await
+    } else if path == "/admin" || path.starts_with("/admin/") {
+        api::handle(req, &cors, |req| admin::router(req, &stores)).await
+    } else {
+        api::handle(req, &cors, |_| async { Ok(api::ApiResponse::not_found()) }).await
+    };
+    log::info!("{} {} {}", method, path, response.status().as_u16());
+    Ok(to_hyper(response))
+}
+
+#[tokio::main]
+async fn main() -> Result<(), DevError> {
+    simple_logger::init_with_level(log::Level::Info)?;
+    let mut args = env::args().skip(1);
+    let dir = args.next().unwrap_or_else(|| "questionnaires".into());
+    let port = match args.next() {
+        Some(port) => port.parse()?,
+        None => 3000,
+    };
+    let stores = Stores::files(&dir);
+    let cors = match env::var("CORS_ALLOWED_ORIGINS") {
+        Ok(_) => Cors::from_env(),
+        Err(_) => Cors::new(vec!["*"]),
+    };
+    let make_service = make_service_fn(move |_| {
+        let (stores, cors) = (stores.clone(), cors.clone());
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                serve(req, stores.clone(), cors.clone())
+            }))
+        }
+    });
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    log::info!("serving questionnaires from {} on http://{}", dir, addr);
+    hyper::Server::bind(&addr).serve(make_service).await?;
+    Ok(())
+}
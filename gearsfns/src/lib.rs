@@ -1,4 +1,10 @@
 #[macro_use]
 extern crate serde;
 
+mod error;
+
+pub mod api;
 pub mod qna;
+pub mod store;
+
+pub use error::Error;
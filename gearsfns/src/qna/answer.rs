@@ -0,0 +1,334 @@
+//! Typed answers, mirroring the `Question` variants they respond to.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime};
+use rust_decimal::Decimal;
+use serde_json::{Map, Number, Value};
+
+use super::dates;
+use super::questionlist::question::{self, SelectionMode};
+use super::questionlist::{Question, QuestionList};
+use super::validation::{ValidationError, Violation};
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Answer {
+    Integer(i32),
+    Number(Decimal),
+    FreeText(String),
+    TrueOrFalse(bool),
+    FixedList(Selection),
+    ArrayOf(Vec<Answer>),
+    Group(AnswerSet),
+    Date(NaiveDate),
+    Time(NaiveTime),
+    DateTime(DateTime<FixedOffset>),
+}
+
+/// The item, or items, picked from a `FixedList`, depending on its mode.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Selection {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+/// The answers to one questionnaire, keyed by question id.
+#[derive(Debug, Serialize, Clone, PartialEq, Default)]
+#[serde(transparent)]
+pub struct AnswerSet {
+    answers: BTreeMap<i32, Answer>,
+}
+
+impl AnswerSet {
+    pub fn get(&self, question_id: i32) -> Option<&Answer> {
+        self.answers.get(&question_id)
+    }
+
+    pub fn get_mut(&mut self, question_id: i32) -> Option<&mut Answer> {
+        self.answers.get_mut(&question_id)
+    }
+
+    pub fn insert(&mut self, question_id: i32, answer: Answer) -> Option<Answer> {
+        self.answers.insert(question_id, answer)
+    }
+
+    pub fn remove(&mut self, question_id: i32) -> Option<Answer> {
+        self.answers.remove(&question_id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (i32, &Answer)> {
+        self.answers.iter().map(|(id, answer)| (*id, answer))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (i32, &mut Answer)> {
+        self.answers.iter_mut().map(|(id, answer)| (*id, answer))
+    }
+
+    pub fn len(&self) -> usize {
+        self.answers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.answers.is_empty()
+    }
+
+    /// The answers as they are submitted, keyed by the property names of
+    /// `questions` rather than their ids.
+    pub fn to_json(&self, questions: &[Question]) -> Value {
+        questions
+            .iter()
+            .filter_map(|q| {
+                let answer = self.get(q.get_id())?;
+                Some((q.property_name(), to_json(q, answer)))
+            })
+            .collect::<Map<String, Value>>()
+            .into()
+    }
+}
+
+fn to_json(question: &Question, answer: &Answer) -> Value {
+    match (question, answer) {
+        (Question::Group(group), Answer::Group(answers)) => answers.to_json(&group.questions),
+        (Question::ArrayOf(array), Answer::ArrayOf(items)) => items
+            .iter()
+            .map(|item| to_json(&array.question, item))
+            .collect(),
+        _ => serde_json::to_value(answer).unwrap_or_default(),
+    }
+}
+
+/// Parses submitted answers, keyed by property name, into an `AnswerSet`.
+///
+/// Only the shape of each answer is checked here: values must have the type
+/// their question expects and name a question of `question_list`. Limits and
+/// required questions are left to the validator.
+pub fn parse(
+    question_list: &QuestionList,
+    value: &Value,
+) -> Result<AnswerSet, Vec<ValidationError>> {
+    let mut parser = Parser {
+        question_list,
+        errors: vec![],
+    };
+    match parser.answers(&question_list.questions, &[], value) {
+        Some(answers) if parser.errors.is_empty() => Ok(answers),
+        _ => Err(parser.errors),
+    }
+}
+
+struct Parser<'a> {
+    question_list: &'a QuestionList,
+    errors: Vec<ValidationError>,
+}
+
+impl Parser<'_> {
+    fn fail(&mut self, path: &[String], violation: Violation) -> Option<Answer> {
+        self.errors
+            .push(ValidationError::new(self.question_list, path, violation));
+        None
+    }
+
+    fn mismatch(&mut self, path: &[String], expected: &str) -> Option<Answer> {
+        self.fail(path, Violation::Type(expected.into()))
+    }
+
+    /// Parses an object of answers, keyed by the ids of `questions`.
+    fn answers(
+        &mut self,
+        questions: &[Question],
+        path: &[String],
+        value: &Value,
+    ) -> Option<AnswerSet> {
+        let object = match value.as_object() {
+            Some(object) => object,
+            None => {
+                self.mismatch(path, "object");
+                return None;
+            }
+        };
+
+        let mut answers = AnswerSet::default();
+        for (key, value) in object {
+            let mut path = path.to_vec();
+            path.push(key.clone());
+            match questions.iter().find(|q| q.property_name() == *key) {
+                Some(question) => {
+                    if let Some(answer) = self.answer(question, &path, value) {
+                        answers.insert(question.get_id(), answer);
+                    }
+                }
+                None => {
+                    self.fail(&path, Violation::AdditionalProperty);
+                }
+            }
+        }
+        Some(answers)
+    }
+
+    fn answer(&mut self, question: &Question, path: &[String], value: &Value) -> Option<Answer> {
+        match (question, value) {
+            (Question::Integer(_), Value::Number(n)) => {
+                match n.as_f64().filter(|f| f.fract() == 0.0) {
+                    Some(f) if f >= i32::MIN as f64 && f <= i32::MAX as f64 => {
+                        Some(Answer::Integer(f as i32))
+                    }
+                    _ => self.mismatch(path, "integer"),
+                }
+            }
+            (Question::Integer(_), _) => self.mismatch(path, "integer"),
+            (Question::Number(_), Value::Number(n)) => match decimal(n) {
+                Some(d) if question::significant_digits(d) > question::MAX_SIGNIFICANT_DIGITS => {
+                    let message = format!(
+                        "must have at most {} significant digits",
+                        question::MAX_SIGNIFICANT_DIGITS
+                    );
+                    self.fail(path, Violation::Other(message))
+                }
+                Some(d) => Some(Answer::Number(d)),
+                None => self.mismatch(path, "number"),
+            },
+            (Question::Number(_), _) => self.mismatch(path, "number"),
+            (Question::FreeText(_), Value::String(s)) => Some(Answer::FreeText(s.clone())),
+            (Question::FreeText(_), _) => self.mismatch(path, "string"),
+            (Question::TrueOrFalse(_), Value::Bool(b)) => Some(Answer::TrueOrFalse(*b)),
+            (Question::TrueOrFalse(_), _) => self.mismatch(path, "boolean"),
+            (Question::FixedList(q), value) => match (q.mode, value) {
+                (SelectionMode::Single, Value::String(s)) => {
+                    Some(Answer::FixedList(Selection::Single(s.clone())))
+                }
+                (SelectionMode::Single, _) => self.mismatch(path, "string"),
+                (SelectionMode::Multiple, Value::Array(items)) => {
+                    let mut selection = Vec::with_capacity(items.len());
+                    for (index, item) in items.iter().enumerate() {
+                        match item {
+                            Value::String(s) => selection.push(s.clone()),
+                            _ => {
+                                let mut path = path.to_vec();
+                                path.push(index.to_string());
+                                self.mismatch(&path, "string");
+                            }
+                        }
+                    }
+                    if selection.len() == items.len() {
+                        Some(Answer::FixedList(Selection::Multiple(selection)))
+                    } else {
+                        None
+                    }
+                }
+                (SelectionMode::Multiple, _) => self.mismatch(path, "array"),
+            },
+            (Question::ArrayOf(array), Value::Array(items)) => {
+                let mut answers = Vec::with_capacity(items.len());
+                let mut valid = true;
+                for (index, item) in items.iter().enumerate() {
+                    let mut path = path.to_vec();
+                    path.push(index.to_string());
+                    match self.answer(&array.question, &path, item) {
+                        Some(answer) => answers.push(answer),
+                        None => valid = false,
+                    }
+                }
+                if valid {
+                    Some(Answer::ArrayOf(answers))
+                } else {
+                    None
+                }
+            }
+            (Question::ArrayOf(_), _) => self.mismatch(path, "array"),
+            (Question::Group(group), value) => self
+                .answers(&group.questions, path, value)
+                .map(Answer::Group),
+            (Question::Date(_), Value::String(s)) => match dates::parse_date(s) {
+                Some(date) => Some(Answer::Date(date)),
+                None => self.fail(path, Violation::Format("date".into())),
+            },
+            (Question::Time(_), Value::String(s)) => match dates::parse_time(s) {
+                Some(time) => Some(Answer::Time(time)),
+                None => self.fail(path, Violation::Pattern(dates::TIME_PATTERN.into())),
+            },
+            (Question::DateTime(_), Value::String(s)) => match DateTime::parse_from_rfc3339(s) {
+                Ok(date_time) => Some(Answer::DateTime(date_time)),
+                Err(_) => self.fail(path, Violation::Format("date-time".into())),
+            },
+            (Question::Date(_), _) | (Question::Time(_), _) | (Question::DateTime(_), _) => {
+                self.mismatch(path, "string")
+            }
+        }
+    }
+}
+
+/// Reads a JSON number through its shortest decimal form, so `0.07` is
+/// exactly 0.07 rather than the nearest binary float.
+pub(crate) fn decimal(n: &Number) -> Option<Decimal> {
+    let text = n.to_string();
+    text.parse()
+        .or_else(|_| Decimal::from_scientific(&text))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qna::questionlist;
+    use serde_json::json;
+
+    #[test]
+    fn parses_typed_answers() {
+        let sample = questionlist::sample_string_questions();
+        let value = json!({
+            "1": "Ice",
+            "8": ["Ice", "Cream"],
+            "88": 10,
+            "222": true,
+            "24": "zork",
+            "25": ["fudge", "nuts"],
+            "dependants": [{ "name": "Ann", "age": 7 }],
+        });
+
+        let answers = parse(&sample, &value).unwrap();
+        assert_eq!(answers.len(), 7);
+        assert_eq!(
+            answers.get(25),
+            Some(&Answer::FixedList(Selection::Multiple(vec![
+                "fudge".into(),
+                "nuts".into()
+            ])))
+        );
+        assert_eq!(answers.get(88), Some(&Answer::Integer(10)));
+        assert_eq!(
+            answers.get(8),
+            Some(&Answer::ArrayOf(vec![
+                Answer::FreeText("Ice".into()),
+                Answer::FreeText("Cream".into())
+            ]))
+        );
+        assert_eq!(answers.to_json(&sample.questions), value);
+    }
+
+    #[test]
+    fn reports_mismatched_and_unknown_answers() {
+        let sample = questionlist::sample_string_questions();
+        let value = json!({
+            "8": ["Ice", false],
+            "88": 1.5,
+            "404": "x",
+        });
+
+        let errors = parse(&sample, &value).unwrap_err();
+        let mut summary: Vec<(&str, &str)> = errors
+            .iter()
+            .map(|e| (e.pointer.as_str(), e.code))
+            .collect();
+        summary.sort();
+        assert_eq!(
+            summary,
+            vec![
+                ("/404", "additionalProperties"),
+                ("/8/1", "type"),
+                ("/88", "type"),
+            ]
+        );
+    }
+}
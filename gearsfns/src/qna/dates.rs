@@ -0,0 +1,275 @@
+//! Calendar helpers for date and time questions: bounds relative to the
+//! current day, and the day it currently is in a questionnaire's timezone.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, TimeZone};
+use chrono_tz::Tz;
+
+/// Wall-clock times are answered without an offset, as `HH:MM` or
+/// `HH:MM:SS`; JSON Schema's `time` format would demand one.
+pub const TIME_PATTERN: &str = "^([01][0-9]|2[0-3]):[0-5][0-9](:[0-5][0-9])?$";
+
+/// How far an offset from today may reach either way, so that a bound
+/// stays well within the dates that can be represented.
+pub const MAX_OFFSET_YEARS: i32 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Unit {
+    Days,
+    Weeks,
+    Months,
+    Years,
+}
+
+impl Unit {
+    /// The most of this unit an offset may count, rounded up.
+    fn max_amount(self) -> i32 {
+        match self {
+            Self::Days => MAX_OFFSET_YEARS * 366,
+            Self::Weeks => MAX_OFFSET_YEARS * 53,
+            Self::Months => MAX_OFFSET_YEARS * 12,
+            Self::Years => MAX_OFFSET_YEARS,
+        }
+    }
+}
+
+/// A limit on a date, either fixed or counted from the day an answer is
+/// checked: `2020-01-31`, `today`, `today+30d` or `today-18y`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum DateBound {
+    Absolute(NaiveDate),
+    Today(i32, Unit),
+}
+
+impl DateBound {
+    /// The date this bound stands for when `today` is the current day, or
+    /// `None` if that is past the dates that can be represented.
+    pub fn resolve(self, today: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Self::Absolute(date) => Some(date),
+            Self::Today(amount, Unit::Days) => {
+                today.checked_add_signed(Duration::days(amount.into()))
+            }
+            Self::Today(amount, Unit::Weeks) => {
+                today.checked_add_signed(Duration::weeks(amount.into()))
+            }
+            Self::Today(amount, Unit::Months) => add_months(today, amount),
+            Self::Today(amount, Unit::Years) => add_months(today, amount.checked_mul(12)?),
+        }
+    }
+}
+
+/// Adds calendar months, clamping to the end of shorter months.
+fn add_months(date: NaiveDate, months: i32) -> Option<NaiveDate> {
+    let total = (date.year() * 12 + date.month0() as i32).checked_add(months)?;
+    let (year, month) = (total.div_euclid(12), total.rem_euclid(12) as u32 + 1);
+    (1..=date.day())
+        .rev()
+        .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseBoundError {
+    Invalid(String),
+    /// The offset reaches further than `MAX_OFFSET_YEARS`.
+    OutOfRange(String),
+}
+
+impl fmt::Display for ParseBoundError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Invalid(s) => write!(
+                f,
+                "\"{}\" is neither a YYYY-MM-DD date nor today, optionally followed by an offset such as +30d or -18y",
+                s
+            ),
+            Self::OutOfRange(s) => write!(
+                f,
+                "\"{}\" reaches more than {} years from today",
+                s, MAX_OFFSET_YEARS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseBoundError {}
+
+impl FromStr for DateBound {
+    type Err = ParseBoundError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseBoundError::Invalid(s.to_string());
+        let offset = match s.strip_prefix("today") {
+            Some(offset) => offset,
+            None => return parse_date(s).map(Self::Absolute).ok_or_else(error),
+        };
+        if offset.is_empty() {
+            return Ok(Self::Today(0, Unit::Days));
+        }
+        if !offset.starts_with(&['+', '-'][..]) {
+            return Err(error());
+        }
+        let (amount, unit) = match offset.char_indices().last() {
+            Some((at, unit)) => (&offset[..at], unit),
+            None => return Err(error()),
+        };
+        let unit = match unit {
+            'd' => Unit::Days,
+            'w' => Unit::Weeks,
+            'm' => Unit::Months,
+            'y' => Unit::Years,
+            _ => return Err(error()),
+        };
+        let amount: i32 = amount.parse().map_err(|_| error())?;
+        let max = unit.max_amount();
+        if !(-max..=max).contains(&amount) {
+            return Err(ParseBoundError::OutOfRange(s.to_string()));
+        }
+        Ok(Self::Today(amount, unit))
+    }
+}
+
+impl fmt::Display for DateBound {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Absolute(date) => write!(f, "{}", date.format("%Y-%m-%d")),
+            Self::Today(0, Unit::Days) => write!(f, "today"),
+            Self::Today(amount, unit) => write!(
+                f,
+                "today{:+}{}",
+                amount,
+                match unit {
+                    Unit::Days => "d",
+                    Unit::Weeks => "w",
+                    Unit::Months => "m",
+                    Unit::Years => "y",
+                }
+            ),
+        }
+    }
+}
+
+impl TryFrom<String> for DateBound {
+    type Error = ParseBoundError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<DateBound> for String {
+    fn from(bound: DateBound) -> Self {
+        bound.to_string()
+    }
+}
+
+/// Parses an IANA timezone name such as `Australia/Brisbane`; questions
+/// without one use UTC.
+pub fn timezone(name: Option<&str>) -> Result<Tz, String> {
+    match name {
+        Some(name) => name.parse(),
+        None => Ok(Tz::UTC),
+    }
+}
+
+/// The date it is at `instant` in `timezone`.
+pub fn date_in<O: TimeZone>(instant: &chrono::DateTime<O>, timezone: Tz) -> NaiveDate {
+    timezone
+        .from_utc_datetime(&instant.naive_utc())
+        .naive_local()
+        .date()
+}
+
+/// Parses a zero-padded `YYYY-MM-DD` date, as the `date` format requires.
+pub fn parse_date(value: &str) -> Option<NaiveDate> {
+    let padded = value.len() == 10
+        && value.bytes().enumerate().all(|(i, b)| match i {
+            4 | 7 => b == b'-',
+            _ => b.is_ascii_digit(),
+        });
+    if padded {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+    } else {
+        None
+    }
+}
+
+/// Parses a wall-clock time matching `TIME_PATTERN`.
+pub fn parse_time(value: &str) -> Option<NaiveTime> {
+    match value.len() {
+        5 => NaiveTime::parse_from_str(value, "%H:%M").ok(),
+        8 => NaiveTime::parse_from_str(value, "%H:%M:%S").ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_and_resolves_bounds() {
+        let today = date("2024-02-29");
+        let cases = vec![
+            ("2020-01-31", date("2020-01-31")),
+            ("today", today),
+            ("today+30d", date("2024-03-30")),
+            ("today-2w", date("2024-02-15")),
+            ("today+1m", date("2024-03-29")),
+            ("today-18y", date("2006-02-28")),
+        ];
+        for (text, expected) in cases {
+            let bound: DateBound = text.parse().unwrap();
+            assert_eq!(bound.resolve(today), Some(expected), "{}", text);
+            assert_eq!(bound.to_string(), text);
+        }
+        assert!("tomorrow".parse::<DateBound>().is_err());
+        assert!("today+d".parse::<DateBound>().is_err());
+        assert!("today+3q".parse::<DateBound>().is_err());
+        assert!("today+é".parse::<DateBound>().is_err());
+        assert!("today+5é".parse::<DateBound>().is_err());
+    }
+
+    #[test]
+    fn offsets_stay_within_range() {
+        let today = date("2024-02-29");
+        assert_eq!(
+            "today+1000y".parse::<DateBound>().unwrap().resolve(today),
+            Some(date("3024-02-29"))
+        );
+        for text in &["today+1001y", "today+2000000000d", "today-200000000y"] {
+            assert!(
+                matches!(
+                    text.parse::<DateBound>(),
+                    Err(ParseBoundError::OutOfRange(_))
+                ),
+                "{}",
+                text
+            );
+        }
+        for unit in &[Unit::Days, Unit::Weeks, Unit::Months, Unit::Years] {
+            assert_eq!(DateBound::Today(i32::MAX, *unit).resolve(today), None);
+            assert_eq!(DateBound::Today(i32::MIN, *unit).resolve(today), None);
+        }
+    }
+
+    #[test]
+    fn dates_follow_the_timezone() {
+        let now: chrono::DateTime<Utc> = "2024-01-01T20:00:00Z".parse().unwrap();
+        assert_eq!(date_in(&now, Tz::UTC), date("2024-01-01"));
+        assert_eq!(
+            date_in(&now, timezone(Some("Australia/Brisbane")).unwrap()),
+            date("2024-01-02")
+        );
+        assert!(timezone(Some("Mars/Olympus_Mons")).is_err());
+    }
+}
@@ -0,0 +1,1153 @@
+//! Authoring checks for a `QuestionList`, catching questionnaires that would
+//! emit a broken schema or could never be answered as intended.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use regex::Regex;
+use serde_json::{Map, Value};
+
+use super::answer;
+use super::dates;
+use super::questionlist::question::{self, ui, DateBound, SelectionMode};
+use super::questionlist::{self, Question, QuestionList};
+use super::validator;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Likely a mistake, but the questionnaire still works.
+    Warning,
+    /// The questionnaire cannot be saved or served.
+    Error,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub question_id: i32,
+    /// JSON pointer to the offending field in the questionnaire document.
+    pub path: String,
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// Checks `question_list` for authoring mistakes.
+pub fn lint(question_list: &QuestionList) -> Vec<Diagnostic> {
+    let mut linter = Linter {
+        diagnostics: vec![],
+        seen: HashMap::new(),
+        keys: HashMap::new(),
+    };
+    for (index, question) in question_list.questions.iter().enumerate() {
+        let path = format!("/questions/{}", index);
+        linter.question(question, &path, true);
+    }
+    for (index, question) in question_list.questions.iter().enumerate() {
+        let path = format!("/questions/{}", index);
+        linter.condition(question_list, question, &path);
+    }
+    linter.diagnostics
+}
+
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(|d| d.severity == Severity::Error)
+}
+
+struct Linter {
+    diagnostics: Vec<Diagnostic>,
+    /// Where each question id was first declared.
+    seen: HashMap<i32, String>,
+    /// Where each question key was first declared.
+    keys: HashMap<String, String>,
+}
+
+impl Linter {
+    fn report(
+        &mut self,
+        severity: Severity,
+        question: &Question,
+        path: String,
+        code: &'static str,
+        message: String,
+    ) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            question_id: question.get_id(),
+            path,
+            code,
+            message,
+        });
+    }
+
+    fn question(&mut self, question: &Question, path: &str, top_level: bool) {
+        let id = question.get_id();
+        let field = |name: &str| format!("{}/{}/{}", path, question.tag(), name);
+
+        match self.seen.get(&id) {
+            Some(first) => {
+                let message = format!("question id {} is already used at {}", id, first);
+                self.report(
+                    Severity::Error,
+                    question,
+                    field("id"),
+                    "duplicate-id",
+                    message,
+                );
+            }
+            None => {
+                self.seen.insert(id, path.to_string());
+            }
+        }
+        if let Some(key) = question.key() {
+            if !question::is_valid_key(key) {
+                let message = format!(
+                    "key \"{}\" must start with a letter or underscore and contain only letters, digits and underscores",
+                    key
+                );
+                self.report(
+                    Severity::Error,
+                    question,
+                    field("key"),
+                    "invalid-key",
+                    message,
+                );
+            }
+            match self.keys.get(key) {
+                Some(first) => {
+                    let message = format!("key \"{}\" is already used at {}", key, first);
+                    self.report(
+                        Severity::Error,
+                        question,
+                        field("key"),
+                        "duplicate-key",
+                        message,
+                    );
+                }
+                None => {
+                    self.keys.insert(key.to_string(), path.to_string());
+                }
+            }
+        }
+        // Neither the schema nor the validator would ever apply it.
+        if !top_level && question.visible_if().is_some() {
+            self.report(
+                Severity::Error,
+                question,
+                field("visible_if"),
+                "condition-ignored",
+                "only top-level questions can be shown conditionally".into(),
+            );
+        }
+
+        match question {
+            Question::Integer(q) => {
+                if let Some(step) = q.step.filter(|step| *step <= 0) {
+                    let message = format!("step must be positive, not {}", step);
+                    self.report(
+                        Severity::Error,
+                        question,
+                        field("step"),
+                        "invalid-step",
+                        message,
+                    );
+                }
+                if let (Some(min), Some(max)) = (q.min, q.max) {
+                    if min > max {
+                        let message = format!("min {} is greater than max {}", min, max);
+                        self.report(
+                            Severity::Error,
+                            question,
+                            field("min"),
+                            "min-exceeds-max",
+                            message,
+                        );
+                    }
+                }
+                if let Some(default) = q.default {
+                    if q.min.is_some_and(|min| default < min)
+                        || q.max.is_some_and(|max| default > max)
+                    {
+                        let message = format!("default {} is outside min and max", default);
+                        self.report(
+                            Severity::Error,
+                            question,
+                            field("default"),
+                            "default-out-of-range",
+                            message,
+                        );
+                    }
+                    if let Some(step) = q.step.filter(|step| *step > 0) {
+                        if default % step != 0 {
+                            let message =
+                                format!("default {} is not a multiple of step {}", default, step);
+                            self.report(
+                                Severity::Error,
+                                question,
+                                field("default"),
+                                "default-unreachable",
+                                message,
+                            );
+                        }
+                    }
+                }
+            }
+            Question::Number(q) => self.number(question, q, &field),
+            Question::FreeText(q) => {
+                for (name, length) in &[("min_length", q.min_length), ("max_length", q.max_length)]
+                {
+                    if let Some(length) = length.filter(|length| *length < 0) {
+                        let message = format!("{} must not be negative, not {}", name, length);
+                        self.report(
+                            Severity::Error,
+                            question,
+                            field(name),
+                            "invalid-length",
+                            message,
+                        );
+                    }
+                }
+                if let (Some(min), Some(max)) = (q.min_length, q.max_length) {
+                    if min > max {
+                        let message =
+                            format!("min_length {} is greater than max_length {}", min, max);
+                        self.report(
+                            Severity::Error,
+                            question,
+                            field("min_length"),
+                            "min-exceeds-max",
+                            message,
+                        );
+                    }
+                }
+                let regex = match q.pattern.as_deref().map(Regex::new) {
+                    Some(Err(err)) => {
+                        let message = format!("pattern does not compile: {}", err);
+                        self.report(
+                            Severity::Error,
+                            question,
+                            field("pattern"),
+                            "invalid-pattern",
+                            message,
+                        );
+                        None
+                    }
+                    Some(Ok(regex)) => Some(regex),
+                    None => None,
+                };
+                if let Some(default) = &q.default {
+                    if regex.is_some_and(|regex| !regex.is_match(default)) {
+                        self.report(
+                            Severity::Error,
+                            question,
+                            field("default"),
+                            "default-invalid",
+                            "default does not match the pattern".into(),
+                        );
+                    }
+                    if let Some(format) =
+                        q.format.filter(|f| !validator::matches_format(*f, default))
+                    {
+                        let message = format!("default is not a valid {}", format.as_str());
+                        self.report(
+                            Severity::Error,
+                            question,
+                            field("default"),
+                            "default-invalid",
+                            message,
+                        );
+                    }
+                    let length = default.chars().count() as i64;
+                    if q.min_length.is_some_and(|min| length < min as i64)
+                        || q.max_length.is_some_and(|max| length > max as i64)
+                    {
+                        let message = format!(
+                            "default is {} characters long, outside min_length and max_length",
+                            length
+                        );
+                        self.report(
+                            Severity::Error,
+                            question,
+                            field("default"),
+                            "default-out-of-range",
+                            message,
+                        );
+                    }
+                }
+                if let Some(ui) = &q.ui {
+                    let ui_field = |name: &str| format!("{}/{}", field("ui"), name);
+                    match ui.rows {
+                        Some(0) => self.report(
+                            Severity::Error,
+                            question,
+                            ui_field("rows"),
+                            "invalid-rows",
+                            "rows must be at least 1".into(),
+                        ),
+                        Some(_) if ui.widget != Some(ui::TextWidget::TextArea) => self.report(
+                            Severity::Warning,
+                            question,
+                            ui_field("rows"),
+                            "option-ignored",
+                            "rows only apply to the textarea widget".into(),
+                        ),
+                        _ => {}
+                    }
+                    if ui.input_type.is_some() && ui.widget.is_some() {
+                        self.report(
+                            Severity::Warning,
+                            question,
+                            ui_field("input_type"),
+                            "option-ignored",
+                            "input_type only applies to single-line text".into(),
+                        );
+                    }
+                }
+            }
+            Question::TrueOrFalse(_) => {}
+            Question::FixedList(q) => {
+                if q.items.is_empty() {
+                    self.report(
+                        Severity::Error,
+                        question,
+                        field("items"),
+                        "no-items",
+                        "a fixed list needs at least one item".into(),
+                    );
+                }
+                if q.item_names.len() != q.items.len() {
+                    let message = format!(
+                        "{} item names given for {} items",
+                        q.item_names.len(),
+                        q.items.len()
+                    );
+                    self.report(
+                        Severity::Error,
+                        question,
+                        field("item_names"),
+                        "item-names-mismatch",
+                        message,
+                    );
+                }
+                for (index, item) in q.items.iter().enumerate() {
+                    if q.items[..index].contains(item) {
+                        let message = format!("item \"{}\" is listed more than once", item);
+                        self.report(
+                            Severity::Warning,
+                            question,
+                            format!("{}/{}", field("items"), index),
+                            "duplicate-item",
+                            message,
+                        );
+                    }
+                }
+                for (index, default) in q.default.iter().enumerate() {
+                    if !q.items.contains(default) {
+                        let message = format!("default \"{}\" is not one of the items", default);
+                        self.report(
+                            Severity::Error,
+                            question,
+                            format!("{}/{}", field("default"), index),
+                            "default-not-in-items",
+                            message,
+                        );
+                    }
+                }
+                match q.mode {
+                    SelectionMode::Single => {
+                        if q.default.len() > 1 {
+                            self.report(
+                                Severity::Warning,
+                                question,
+                                field("default"),
+                                "multiple-defaults",
+                                "only the first default is used".into(),
+                            );
+                        }
+                        if q.min_selections.is_some() || q.max_selections.is_some() {
+                            self.report(
+                                Severity::Warning,
+                                question,
+                                field("mode"),
+                                "selections-ignored",
+                                "min_selections and max_selections only apply to multiple mode"
+                                    .into(),
+                            );
+                        }
+                    }
+                    SelectionMode::Multiple => self.selections(question, q, &field),
+                }
+                if let Some(ui) = &q.ui {
+                    for (index, item) in ui.enum_disabled.iter().enumerate() {
+                        if !q.items.contains(item) {
+                            let message =
+                                format!("disabled item \"{}\" is not one of the items", item);
+                            self.report(
+                                Severity::Warning,
+                                question,
+                                format!("{}/enum_disabled/{}", field("ui"), index),
+                                "disabled-item-not-in-items",
+                                message,
+                            );
+                        }
+                    }
+                    if q.required
+                        && !q.items.is_empty()
+                        && q.items.iter().all(|item| ui.enum_disabled.contains(item))
+                    {
+                        self.report(
+                            Severity::Error,
+                            question,
+                            format!("{}/enum_disabled", field("ui")),
+                            "no-selectable-items",
+                            "every item is disabled, so the question cannot be answered".into(),
+                        );
+                    }
+                }
+            }
+            Question::ArrayOf(q) => {
+                self.question(&q.question, &field("question"), false);
+                for (name, count) in &[("min_items", q.min_items), ("max_items", q.max_items)] {
+                    if let Some(count) = count.filter(|count| *count < 0) {
+                        let message = format!("{} must not be negative, not {}", name, count);
+                        self.report(
+                            Severity::Error,
+                            question,
+                            field(name),
+                            "invalid-items",
+                            message,
+                        );
+                    }
+                }
+                if let (Some(min), Some(max)) = (q.min_items, q.max_items) {
+                    if min > max {
+                        let message =
+                            format!("min_items {} is greater than max_items {}", min, max);
+                        self.report(
+                            Severity::Error,
+                            question,
+                            field("min_items"),
+                            "min-exceeds-max",
+                            message,
+                        );
+                    }
+                }
+            }
+            Question::Group(q) => {
+                if q.questions.is_empty() {
+                    self.report(
+                        Severity::Error,
+                        question,
+                        field("questions"),
+                        "no-questions",
+                        "a group needs at least one question".into(),
+                    );
+                }
+                for (index, member) in q.questions.iter().enumerate() {
+                    self.question(member, &format!("{}/{}", field("questions"), index), false);
+                }
+            }
+            Question::Date(q) => {
+                if self
+                    .timezone(question, q.timezone.as_deref(), &field)
+                    .is_some()
+                {
+                    self.date_bounds(question, (q.min, q.max), q.default, &field);
+                }
+            }
+            Question::DateTime(q) => {
+                if let Some(timezone) = self.timezone(question, q.timezone.as_deref(), &field) {
+                    let default = q.default.map(|dt| dates::date_in(&dt, timezone));
+                    self.date_bounds(question, (q.min, q.max), default, &field);
+                }
+            }
+            Question::Time(q) => {
+                if let (Some(min), Some(max)) = (q.min, q.max) {
+                    if min > max {
+                        let message = format!("min {} is later than max {}", min, max);
+                        self.report(
+                            Severity::Error,
+                            question,
+                            field("min"),
+                            "min-exceeds-max",
+                            message,
+                        );
+                    }
+                }
+                if let Some(default) = q.default {
+                    if q.min.is_some_and(|min| default < min)
+                        || q.max.is_some_and(|max| default > max)
+                    {
+                        let message = format!("default {} is outside min and max", default);
+                        self.report(
+                            Severity::Error,
+                            question,
+                            field("default"),
+                            "default-out-of-range",
+                            message,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn number(
+        &mut self,
+        question: &Question,
+        q: &question::Number,
+        field: &dyn Fn(&str) -> String,
+    ) {
+        if let Some(precision) = q.precision.filter(|p| *p > question::MAX_PRECISION) {
+            let message = format!(
+                "precision must be at most {}, not {}",
+                question::MAX_PRECISION,
+                precision
+            );
+            self.report(
+                Severity::Error,
+                question,
+                field("precision"),
+                "invalid-precision",
+                message,
+            );
+        }
+        if let Some(step) = q.step {
+            if step.is_sign_negative() || step.is_zero() {
+                let message = format!("step must be positive, not {}", step);
+                self.report(
+                    Severity::Error,
+                    question,
+                    field("step"),
+                    "invalid-step",
+                    message,
+                );
+            } else if let Some(precision) = q.precision.filter(|p| step.normalize().scale() > *p) {
+                let message = format!(
+                    "step {} has more than the {} decimal places precision allows",
+                    step, precision
+                );
+                self.report(
+                    Severity::Error,
+                    question,
+                    field("step"),
+                    "invalid-step",
+                    message,
+                );
+            }
+        }
+        let values = [
+            ("default", q.default),
+            ("step", q.step),
+            ("min", q.min),
+            ("max", q.max),
+        ];
+        for (name, value) in values.iter() {
+            let too_long = value.filter(|value| {
+                question::significant_digits(*value) > question::MAX_SIGNIFICANT_DIGITS
+            });
+            if let Some(value) = too_long {
+                let message = format!(
+                    "{} {} has more than {} significant digits, so it would not be stored exactly",
+                    name,
+                    value,
+                    question::MAX_SIGNIFICANT_DIGITS
+                );
+                self.report(
+                    Severity::Error,
+                    question,
+                    field(name),
+                    "too-many-digits",
+                    message,
+                );
+            }
+        }
+        if let (Some(min), Some(max)) = (q.min, q.max) {
+            if min > max {
+                let message = format!("min {} is greater than max {}", min, max);
+                self.report(
+                    Severity::Error,
+                    question,
+                    field("min"),
+                    "min-exceeds-max",
+                    message,
+                );
+            }
+        }
+        if let Some(default) = q.default {
+            if q.min.is_some_and(|min| default < min) || q.max.is_some_and(|max| default > max) {
+                let message = format!("default {} is outside min and max", default);
+                self.report(
+                    Severity::Error,
+                    question,
+                    field("default"),
+                    "default-out-of-range",
+                    message,
+                );
+            }
+            let multiple_of = q
+                .multiple_of()
+                .filter(|step| step.is_sign_positive() && !step.is_zero());
+            if let Some(step) = multiple_of.filter(|step| !(default % *step).is_zero()) {
+                let message = format!("default {} is not a multiple of {}", default, step);
+                self.report(
+                    Severity::Error,
+                    question,
+                    field("default"),
+                    "default-unreachable",
+                    message,
+                );
+            }
+        }
+    }
+
+    /// Reports a timezone that is not a known IANA name.
+    fn timezone(
+        &mut self,
+        question: &Question,
+        name: Option<&str>,
+        field: &dyn Fn(&str) -> String,
+    ) -> Option<Tz> {
+        match dates::timezone(name) {
+            Ok(timezone) => Some(timezone),
+            Err(err) => {
+                self.report(
+                    Severity::Error,
+                    question,
+                    field("timezone"),
+                    "invalid-timezone",
+                    err,
+                );
+                None
+            }
+        }
+    }
+
+    /// Checks bounds that can be compared without knowing the current day:
+    /// two fixed dates, or two offsets from today in the same unit.
+    fn date_bounds(
+        &mut self,
+        question: &Question,
+        (min, max): (Option<DateBound>, Option<DateBound>),
+        default: Option<NaiveDate>,
+        field: &dyn Fn(&str) -> String,
+    ) {
+        let inverted = match (min, max) {
+            (Some(DateBound::Absolute(min)), Some(DateBound::Absolute(max))) => min > max,
+            (Some(DateBound::Today(min, unit)), Some(DateBound::Today(max, max_unit))) => {
+                unit == max_unit && min > max
+            }
+            _ => false,
+        };
+        if let (true, Some(min), Some(max)) = (inverted, min, max) {
+            let message = format!("min {} is later than max {}", min, max);
+            self.report(
+                Severity::Error,
+                question,
+                field("min"),
+                "min-exceeds-max",
+                message,
+            );
+        }
+        if let Some(default) = default {
+            let fixed = |bound: Option<DateBound>| match bound {
+                Some(DateBound::Absolute(date)) => Some(date),
+                _ => None,
+            };
+            if fixed(min).is_some_and(|min| default < min)
+                || fixed(max).is_some_and(|max| default > max)
+            {
+                let message = format!("default {} is outside min and max", default);
+                self.report(
+                    Severity::Error,
+                    question,
+                    field("default"),
+                    "default-out-of-range",
+                    message,
+                );
+            }
+        }
+    }
+
+    fn selections(
+        &mut self,
+        question: &Question,
+        q: &question::FixedList,
+        field: &dyn Fn(&str) -> String,
+    ) {
+        for (name, count) in &[
+            ("min_selections", q.min_selections),
+            ("max_selections", q.max_selections),
+        ] {
+            if let Some(count) = count.filter(|count| *count < 0) {
+                let message = format!("{} must not be negative, not {}", name, count);
+                self.report(
+                    Severity::Error,
+                    question,
+                    field(name),
+                    "invalid-selections",
+                    message,
+                );
+            }
+        }
+        if let (Some(min), Some(max)) = (q.min_selections, q.max_selections) {
+            if min > max {
+                let message = format!(
+                    "min_selections {} is greater than max_selections {}",
+                    min, max
+                );
+                self.report(
+                    Severity::Error,
+                    question,
+                    field("min_selections"),
+                    "min-exceeds-max",
+                    message,
+                );
+            }
+        }
+        if let Some(min) = q.min_selections {
+            if min as i64 > q.items.len() as i64 {
+                let message = format!(
+                    "min_selections {} is more than the {} items to pick from",
+                    min,
+                    q.items.len()
+                );
+                self.report(
+                    Severity::Error,
+                    question,
+                    field("min_selections"),
+                    "unsatisfiable-selections",
+                    message,
+                );
+            }
+        }
+        let count = q.default.len() as i64;
+        if !q.default.is_empty()
+            && (q.min_selections.is_some_and(|min| count < min as i64)
+                || q.max_selections.is_some_and(|max| count > max as i64))
+        {
+            let message = format!(
+                "default picks {} items, outside min_selections and max_selections",
+                count
+            );
+            self.report(
+                Severity::Error,
+                question,
+                field("default"),
+                "default-out-of-range",
+                message,
+            );
+        }
+    }
+
+    fn condition(&mut self, question_list: &QuestionList, question: &Question, path: &str) {
+        let condition = match question.visible_if() {
+            Some(condition) => condition,
+            None => return,
+        };
+        let field = format!("{}/{}/visible_if/question_id", path, question.tag());
+
+        if condition.question_id == question.get_id() {
+            self.report(
+                Severity::Error,
+                question,
+                field,
+                "self-condition",
+                "a question cannot depend on its own answer".into(),
+            );
+            return;
+        }
+        if !question_list
+            .questions
+            .iter()
+            .any(|q| q.get_id() == condition.question_id)
+        {
+            let message = format!("question {} does not exist", condition.question_id);
+            self.report(
+                Severity::Error,
+                question,
+                field,
+                "unknown-condition-target",
+                message,
+            );
+            return;
+        }
+
+        let mut answers = Map::new();
+        answers.insert(
+            questionlist::property_name(&question_list.questions, condition.question_id),
+            condition.equals.clone(),
+        );
+        if answer::parse(question_list, &Value::Object(answers)).is_err() {
+            let message = format!(
+                "question {} can never be answered with {}",
+                condition.question_id, condition.equals
+            );
+            self.report(
+                Severity::Warning,
+                question,
+                format!("{}/{}/visible_if/equals", path, question.tag()),
+                "condition-never-holds",
+                message,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qna::questionlist;
+
+    fn codes(diagnostics: &[Diagnostic]) -> Vec<(&str, &'static str, Severity)> {
+        diagnostics
+            .iter()
+            .map(|d| (d.path.as_str(), d.code, d.severity))
+            .collect()
+    }
+
+    #[test]
+    fn sample_is_clean() {
+        assert_eq!(lint(&questionlist::sample_string_questions()), vec![]);
+    }
+
+    #[test]
+    fn reports_authoring_mistakes() {
+        let mut sample = questionlist::sample_string_questions();
+        for question in sample.questions.iter_mut() {
+            match question {
+                Question::ArrayOf(q) => {
+                    if let Question::FreeText(item) = &mut q.question {
+                        item.id = 1;
+                    }
+                }
+                Question::Integer(q) => {
+                    q.min = Some(50);
+                    q.max = Some(40);
+                    q.default = Some(45);
+                }
+                Question::FixedList(q) if q.id == 24 => {
+                    q.item_names.pop();
+                    q.default = vec!["dork".into()];
+                }
+                Question::FixedList(q) => {
+                    q.min_selections = Some(3);
+                    q.max_selections = Some(2);
+                }
+                Question::FreeText(q) if q.id == 3 => {
+                    q.visible_if = Some(question::Condition {
+                        question_id: 222,
+                        equals: "yes".into(),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        let diagnostics = lint(&sample);
+        assert!(has_errors(&diagnostics));
+        assert_eq!(
+            codes(&diagnostics),
+            vec![
+                (
+                    "/questions/1/ArrayOf/question/FreeText/id",
+                    "duplicate-id",
+                    Severity::Error
+                ),
+                (
+                    "/questions/2/Integer/min",
+                    "min-exceeds-max",
+                    Severity::Error
+                ),
+                (
+                    "/questions/2/Integer/default",
+                    "default-out-of-range",
+                    Severity::Error
+                ),
+                (
+                    "/questions/2/Integer/default",
+                    "default-unreachable",
+                    Severity::Error
+                ),
+                (
+                    "/questions/4/FixedList/item_names",
+                    "item-names-mismatch",
+                    Severity::Error
+                ),
+                (
+                    "/questions/4/FixedList/default/0",
+                    "default-not-in-items",
+                    Severity::Error
+                ),
+                (
+                    "/questions/7/FixedList/min_selections",
+                    "min-exceeds-max",
+                    Severity::Error
+                ),
+                (
+                    "/questions/7/FixedList/default",
+                    "default-out-of-range",
+                    Severity::Error
+                ),
+                (
+                    "/questions/5/FreeText/visible_if/equals",
+                    "condition-never-holds",
+                    Severity::Warning
+                ),
+            ]
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "question id 1 is already used at /questions/0"
+        );
+    }
+
+    #[test]
+    fn reports_invalid_patterns_and_defaults() {
+        let mut sample = questionlist::sample_string_questions();
+        if let Question::FreeText(q) = &mut sample.questions[0] {
+            q.pattern = Some("(".into());
+            q.format = Some(question::TextFormat::Email);
+        }
+
+        assert_eq!(
+            codes(&lint(&sample)),
+            vec![
+                (
+                    "/questions/0/FreeText/pattern",
+                    "invalid-pattern",
+                    Severity::Error
+                ),
+                (
+                    "/questions/0/FreeText/default",
+                    "default-invalid",
+                    Severity::Error
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_invalid_and_duplicate_keys() {
+        let mut sample = questionlist::sample_string_questions();
+        if let Question::Integer(q) = &mut sample.questions[2] {
+            q.key = Some("2nd-number".into());
+        }
+        if let Question::TrueOrFalse(q) = &mut sample.questions[3] {
+            q.key = Some("name".into());
+        }
+
+        assert_eq!(
+            codes(&lint(&sample)),
+            vec![
+                ("/questions/2/Integer/key", "invalid-key", Severity::Error),
+                (
+                    "/questions/8/ArrayOf/question/Group/questions/0/FreeText/key",
+                    "duplicate-key",
+                    Severity::Error
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_group_mistakes() {
+        let mut sample = questionlist::sample_string_questions();
+        if let Question::ArrayOf(q) = &mut sample.questions[8] {
+            q.min_items = Some(12);
+            if let Question::Group(group) = &mut q.question {
+                group.questions[1] = Question::Group(question::Group {
+                    id: 1,
+                    key: None,
+                    title: "Empty".into(),
+                    description: "".into(),
+                    required: false,
+                    visible_if: None,
+                    questions: vec![],
+                    ui: None,
+                });
+                if let Question::FreeText(name) = &mut group.questions[0] {
+                    name.visible_if = Some(question::Condition {
+                        question_id: 222,
+                        equals: true.into(),
+                    });
+                }
+            }
+        }
+
+        assert_eq!(
+            codes(&lint(&sample)),
+            vec![
+                (
+                    "/questions/8/ArrayOf/question/Group/questions/0/FreeText/visible_if",
+                    "condition-ignored",
+                    Severity::Error
+                ),
+                (
+                    "/questions/8/ArrayOf/question/Group/questions/1/Group/id",
+                    "duplicate-id",
+                    Severity::Error
+                ),
+                (
+                    "/questions/8/ArrayOf/question/Group/questions/1/Group/questions",
+                    "no-questions",
+                    Severity::Error
+                ),
+                (
+                    "/questions/8/ArrayOf/min_items",
+                    "min-exceeds-max",
+                    Severity::Error
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_number_mistakes() {
+        let question_list: QuestionList = serde_json::from_value(serde_json::json!({
+            "title": "Shopping",
+            "description": "",
+            "questions": [
+                { "Number": { "id": 1, "title": "Price", "description": "", "required": true,
+                    "visible_if": null, "default": 0.127, "precision": 2, "step": 0.005,
+                    "min": 0, "max": 100, "unit": "AUD" } },
+                { "Number": { "id": 2, "title": "Weight", "description": "", "required": true,
+                    "visible_if": null, "default": 3.3, "precision": 30, "step": null,
+                    "min": 5, "max": 1, "unit": "kg" } },
+                { "Number": { "id": 3, "title": "Ratio", "description": "", "required": true,
+                    "visible_if": null, "default": null, "precision": null, "step": null,
+                    "min": 0.123456789012345, "max": 0.1234567890123456, "unit": null } },
+            ],
+        }))
+        .unwrap();
+
+        assert_eq!(
+            codes(&lint(&question_list)),
+            vec![
+                ("/questions/0/Number/step", "invalid-step", Severity::Error),
+                (
+                    "/questions/0/Number/default",
+                    "default-unreachable",
+                    Severity::Error
+                ),
+                (
+                    "/questions/1/Number/precision",
+                    "invalid-precision",
+                    Severity::Error
+                ),
+                (
+                    "/questions/1/Number/min",
+                    "min-exceeds-max",
+                    Severity::Error
+                ),
+                (
+                    "/questions/1/Number/default",
+                    "default-out-of-range",
+                    Severity::Error
+                ),
+                (
+                    "/questions/2/Number/max",
+                    "too-many-digits",
+                    Severity::Error
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_date_mistakes() {
+        let question_list: QuestionList = serde_json::from_value(serde_json::json!({
+            "title": "Appointments",
+            "description": "",
+            "questions": [
+                { "Date": { "id": 1, "title": "Born", "description": "", "required": true,
+                    "visible_if": null, "default": "1850-01-01", "min": "1900-01-01",
+                    "max": "today-18y", "timezone": null } },
+                { "DateTime": { "id": 2, "title": "Slot", "description": "", "required": true,
+                    "visible_if": null, "default": null, "min": "today+2w", "max": "today+1w",
+                    "timezone": "Moon/Tranquility" } },
+                { "Time": { "id": 3, "title": "Call me at", "description": "", "required": false,
+                    "visible_if": null, "default": "08:00:00", "min": "17:00:00",
+                    "max": "09:00:00" } },
+            ],
+        }))
+        .unwrap();
+
+        assert_eq!(
+            codes(&lint(&question_list)),
+            vec![
+                (
+                    "/questions/0/Date/default",
+                    "default-out-of-range",
+                    Severity::Error
+                ),
+                (
+                    "/questions/1/DateTime/timezone",
+                    "invalid-timezone",
+                    Severity::Error
+                ),
+                ("/questions/2/Time/min", "min-exceeds-max", Severity::Error),
+                (
+                    "/questions/2/Time/default",
+                    "default-out-of-range",
+                    Severity::Error
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_ui_mistakes() {
+        let question_list: QuestionList = serde_json::from_value(serde_json::json!({
+            "title": "Contact",
+            "description": "",
+            "questions": [
+                { "FreeText": { "id": 1, "title": "Phone", "description": "", "default": null,
+                    "required": true, "visible_if": null, "min_length": null, "max_length": null,
+                    "pattern": null, "format": null,
+                    "ui": { "widget": "password", "rows": 3, "input_type": "tel" } } },
+                { "FreeText": { "id": 2, "title": "Notes", "description": "", "default": null,
+                    "required": false, "visible_if": null, "min_length": null, "max_length": null,
+                    "pattern": null, "format": null, "ui": { "widget": "textarea", "rows": 0 } } },
+                { "FixedList": { "id": 3, "title": "Size", "description": "", "required": true,
+                    "visible_if": null, "default": [], "items": ["s", "m"], "item_names": ["S", "M"],
+                    "min_selections": null, "max_selections": null,
+                    "ui": { "widget": null, "enum_disabled": ["s", "m", "xl"] } } },
+            ],
+        }))
+        .unwrap();
+
+        assert_eq!(
+            codes(&lint(&question_list)),
+            vec![
+                (
+                    "/questions/0/FreeText/ui/rows",
+                    "option-ignored",
+                    Severity::Warning
+                ),
+                (
+                    "/questions/0/FreeText/ui/input_type",
+                    "option-ignored",
+                    Severity::Warning
+                ),
+                (
+                    "/questions/1/FreeText/ui/rows",
+                    "invalid-rows",
+                    Severity::Error
+                ),
+                (
+                    "/questions/2/FixedList/ui/enum_disabled/2",
+                    "disabled-item-not-in-items",
+                    Severity::Warning
+                ),
+                (
+                    "/questions/2/FixedList/ui/enum_disabled",
+                    "no-selectable-items",
+                    Severity::Error
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,695 @@
+//! Moving stored answers from one revision of a questionnaire to another.
+//!
+//! `diff` lists what changed between two revisions. Answers that no longer
+//! fit are carried over by the author's `Rule`s: renaming a `FixedList`
+//! item, dropping the answers to a question, or filling in an answer to a
+//! question the earlier revision did not have. A `Plan` applies the rules to
+//! one submission at a time and checks the result against the later
+//! revision, so nothing is rewritten into answers a taker could not submit.
+
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+
+use super::answer::{self, Answer, AnswerSet, Selection};
+use super::questionlist::{self, Question, QuestionList};
+use super::validation::ValidationError;
+use super::validator;
+
+/// A difference between two revisions that can affect stored answers.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "change", rename_all = "kebab-case")]
+pub enum Change {
+    #[serde(rename_all = "camelCase")]
+    QuestionAdded { question_id: i32, required: bool },
+    #[serde(rename_all = "camelCase")]
+    QuestionRemoved { question_id: i32 },
+    /// The question is asked with another variant, such as `FreeText`
+    /// becoming `Integer`.
+    #[serde(rename_all = "camelCase")]
+    TypeChanged {
+        question_id: i32,
+        from: &'static str,
+        to: &'static str,
+    },
+    /// Answers are submitted under another property name.
+    #[serde(rename_all = "camelCase")]
+    KeyChanged {
+        question_id: i32,
+        from: String,
+        to: String,
+    },
+    /// The question moved into or out of a `Group` or `ArrayOf`; `None` is
+    /// the top level. Its answers are no longer found where they were given.
+    #[serde(rename_all = "camelCase")]
+    ParentChanged {
+        question_id: i32,
+        from: Option<i32>,
+        to: Option<i32>,
+    },
+    #[serde(rename_all = "camelCase")]
+    ItemsRemoved {
+        question_id: i32,
+        items: Vec<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    ItemsAdded {
+        question_id: i32,
+        items: Vec<String>,
+    },
+    /// A bound was raised or lowered so that fewer answers fit; only the
+    /// bounds that narrowed are given.
+    #[serde(rename_all = "camelCase")]
+    RangeNarrowed {
+        question_id: i32,
+        min: Option<String>,
+        max: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    BecameRequired { question_id: i32 },
+}
+
+/// Every question, nested ones included, by id.
+fn flatten(questions: &[Question]) -> Vec<&Question> {
+    let mut flat = vec![];
+    for question in questions {
+        flat.push(question);
+        match question {
+            Question::ArrayOf(array) => flat.extend(flatten(std::slice::from_ref(&array.question))),
+            Question::Group(group) => flat.extend(flatten(&group.questions)),
+            _ => {}
+        }
+    }
+    flat
+}
+
+/// The id of the question every question is nested in, by id; `None` for
+/// top-level questions.
+fn parents(questions: &[Question], parent: Option<i32>, map: &mut HashMap<i32, Option<i32>>) {
+    for question in questions {
+        map.insert(question.get_id(), parent);
+        let id = Some(question.get_id());
+        match question {
+            Question::ArrayOf(array) => parents(std::slice::from_ref(&array.question), id, map),
+            Question::Group(group) => parents(&group.questions, id, map),
+            _ => {}
+        }
+    }
+}
+
+fn parent_map(questions: &[Question]) -> HashMap<i32, Option<i32>> {
+    let mut map = HashMap::new();
+    parents(questions, None, &mut map);
+    map
+}
+
+fn find(questions: &[Question], id: i32) -> Option<&Question> {
+    flatten(questions).into_iter().find(|q| q.get_id() == id)
+}
+
+/// The bound of `to` if it admits fewer values than that of `from`; `lower`
+/// tells a minimum from a maximum.
+fn narrowed<T: PartialOrd + ToString>(
+    from: Option<T>,
+    to: Option<T>,
+    lower: bool,
+) -> Option<String> {
+    match (from, to) {
+        (_, None) => None,
+        (None, Some(to)) => Some(to.to_string()),
+        (Some(from), Some(to)) if (lower && to > from) || (!lower && to < from) => {
+            Some(to.to_string())
+        }
+        _ => None,
+    }
+}
+
+fn range_narrowed(from: &Question, to: &Question) -> Option<Change> {
+    let (min, max) = match (from, to) {
+        (Question::Integer(from), Question::Integer(to)) => (
+            narrowed(from.min, to.min, true),
+            narrowed(from.max, to.max, false),
+        ),
+        (Question::Number(from), Question::Number(to)) => (
+            narrowed(from.min, to.min, true),
+            narrowed(from.max, to.max, false),
+        ),
+        _ => return None,
+    };
+    if min.is_none() && max.is_none() {
+        return None;
+    }
+    Some(Change::RangeNarrowed {
+        question_id: to.get_id(),
+        min,
+        max,
+    })
+}
+
+/// Changes between revisions `from` and `to`, matching questions by id
+/// wherever they are nested.
+pub fn diff(from: &QuestionList, to: &QuestionList) -> Vec<Change> {
+    let before = flatten(&from.questions);
+    let after = flatten(&to.questions);
+    let (parents_before, parents_after) = (parent_map(&from.questions), parent_map(&to.questions));
+    let mut changes = vec![];
+    for old in &before {
+        let id = old.get_id();
+        let new = match after.iter().find(|q| q.get_id() == id) {
+            Some(new) => new,
+            None => {
+                changes.push(Change::QuestionRemoved { question_id: id });
+                continue;
+            }
+        };
+        if old.tag() != new.tag() {
+            changes.push(Change::TypeChanged {
+                question_id: id,
+                from: old.tag(),
+                to: new.tag(),
+            });
+            continue;
+        }
+        if parents_before[&id] != parents_after[&id] {
+            changes.push(Change::ParentChanged {
+                question_id: id,
+                from: parents_before[&id],
+                to: parents_after[&id],
+            });
+        }
+        if old.property_name() != new.property_name() {
+            changes.push(Change::KeyChanged {
+                question_id: id,
+                from: old.property_name(),
+                to: new.property_name(),
+            });
+        }
+        if let (Question::FixedList(old), Question::FixedList(new)) = (old, new) {
+            let removed: Vec<String> = old
+                .items
+                .iter()
+                .filter(|item| !new.items.contains(item))
+                .cloned()
+                .collect();
+            let added: Vec<String> = new
+                .items
+                .iter()
+                .filter(|item| !old.items.contains(item))
+                .cloned()
+                .collect();
+            if !removed.is_empty() {
+                changes.push(Change::ItemsRemoved {
+                    question_id: id,
+                    items: removed,
+                });
+            }
+            if !added.is_empty() {
+                changes.push(Change::ItemsAdded {
+                    question_id: id,
+                    items: added,
+                });
+            }
+        }
+        changes.extend(range_narrowed(old, new));
+        if new.is_required() && !old.is_required() {
+            changes.push(Change::BecameRequired { question_id: id });
+        }
+    }
+    for new in after {
+        if !before.iter().any(|q| q.get_id() == new.get_id()) {
+            changes.push(Change::QuestionAdded {
+                question_id: new.get_id(),
+                required: new.is_required(),
+            });
+        }
+    }
+    changes
+}
+
+/// How an author carries answers over to the later revision.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "rule", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum Rule {
+    /// Replaces item `from` with `to` wherever it was picked.
+    #[serde(rename_all = "camelCase")]
+    RenameItem {
+        question_id: i32,
+        from: String,
+        to: String,
+    },
+    /// Discards the answers to a question.
+    #[serde(rename_all = "camelCase")]
+    DropQuestion { question_id: i32 },
+    /// Answers a top-level question of the later revision with `value`, as
+    /// it would be submitted, where the answers have none.
+    #[serde(rename_all = "camelCase")]
+    Fill { question_id: i32, value: Value },
+}
+
+/// A rule that cannot be applied between the two revisions.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct RuleError {
+    /// Position of the rule in the list it was given in.
+    pub rule: usize,
+    pub message: String,
+}
+
+/// Why the answers of a submission could not be migrated.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "reason", rename_all = "kebab-case")]
+pub enum Failure {
+    /// The answers do not fit the revision they were submitted against.
+    Unreadable { errors: Vec<ValidationError> },
+    /// Questions answered that the later revision no longer has, or has
+    /// nested elsewhere, and that no rule drops.
+    #[serde(rename_all = "camelCase")]
+    Undropped { question_ids: Vec<i32> },
+    /// The migrated answers break the rules of the later revision.
+    Invalid { errors: Vec<ValidationError> },
+}
+
+/// Checked rules for migrating answers from revision `from` to `to`.
+#[derive(Debug)]
+pub struct Plan<'a> {
+    from: &'a QuestionList,
+    to: &'a QuestionList,
+    rules: Vec<Rule>,
+}
+
+impl<'a> Plan<'a> {
+    /// Checks that every rule names a question it applies to.
+    pub fn new(
+        from: &'a QuestionList,
+        to: &'a QuestionList,
+        rules: Vec<Rule>,
+    ) -> Result<Self, Vec<RuleError>> {
+        let errors: Vec<RuleError> = rules
+            .iter()
+            .enumerate()
+            .filter_map(|(index, rule)| {
+                check_rule(from, to, rule).err().map(|message| RuleError {
+                    rule: index,
+                    message,
+                })
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(Self { from, to, rules })
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// The answers of a submission made at `submitted_at`, as they would
+    /// have been submitted against the later revision. Relative date bounds
+    /// are resolved as of `submitted_at`, as they were when it was made.
+    pub fn migrate(&self, answers: &Value, submitted_at: DateTime<Utc>) -> Result<Value, Failure> {
+        let mut answers =
+            answer::parse(self.from, answers).map_err(|errors| Failure::Unreadable { errors })?;
+        self.rewrite(&self.from.questions, &mut answers);
+
+        // A question that moved keeps no answers, since they are read from
+        // where the earlier revision nested it.
+        let before = parent_map(&self.from.questions);
+        let kept: BTreeSet<i32> = parent_map(&self.to.questions)
+            .into_iter()
+            .filter(|(id, parent)| before.get(id) == Some(parent))
+            .map(|(id, _)| id)
+            .collect();
+        let mut answered = BTreeSet::new();
+        answered_ids(&self.from.questions, &answers, &mut answered);
+        let question_ids: Vec<i32> = answered.difference(&kept).copied().collect();
+        if !question_ids.is_empty() {
+            return Err(Failure::Undropped { question_ids });
+        }
+
+        let mut value = answers.to_json(&self.to.questions);
+        if let Value::Object(object) = &mut value {
+            self.fill(object);
+        }
+        let answers = answer::parse(self.to, &value)
+            .and_then(|answers| {
+                validator::validate_at(self.to, &answers, submitted_at)?;
+                Ok(answers)
+            })
+            .map_err(|errors| Failure::Invalid { errors })?;
+        Ok(answers.to_json(&self.to.questions))
+    }
+
+    /// Applies the rename and drop rules to `answers` to `questions`, and to
+    /// the answers nested in them.
+    fn rewrite(&self, questions: &[Question], answers: &mut AnswerSet) {
+        for rule in &self.rules {
+            if let Rule::DropQuestion { question_id } = rule {
+                answers.remove(*question_id);
+            }
+        }
+        for (id, answer) in answers.iter_mut() {
+            if let Some(question) = questions.iter().find(|q| q.get_id() == id) {
+                self.rewrite_answer(question, answer);
+            }
+        }
+    }
+
+    fn rewrite_answer(&self, question: &Question, answer: &mut Answer) {
+        match (question, answer) {
+            (Question::Group(group), Answer::Group(answers)) => {
+                self.rewrite(&group.questions, answers)
+            }
+            (Question::ArrayOf(array), Answer::ArrayOf(items)) => {
+                for item in items {
+                    self.rewrite_answer(&array.question, item);
+                }
+            }
+            (Question::FixedList(list), Answer::FixedList(selection)) => {
+                let renames: HashMap<&str, &str> = self
+                    .rules
+                    .iter()
+                    .filter_map(|rule| match rule {
+                        Rule::RenameItem {
+                            question_id,
+                            from,
+                            to,
+                        } if *question_id == list.id => Some((from.as_str(), to.as_str())),
+                        _ => None,
+                    })
+                    .collect();
+                let rename = |item: &mut String| {
+                    if let Some(to) = renames.get(item.as_str()) {
+                        *item = to.to_string();
+                    }
+                };
+                match selection {
+                    Selection::Single(item) => rename(item),
+                    Selection::Multiple(items) => {
+                        items.iter_mut().for_each(rename);
+                        // Two items renamed to one are picked once.
+                        let mut seen = BTreeSet::new();
+                        items.retain(|item| seen.insert(item.clone()));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Answers the questions of the fill rules left unanswered.
+    fn fill(&self, answers: &mut Map<String, Value>) {
+        for rule in &self.rules {
+            if let Rule::Fill { question_id, value } = rule {
+                answers
+                    .entry(questionlist::property_name(
+                        &self.to.questions,
+                        *question_id,
+                    ))
+                    .or_insert_with(|| value.clone());
+            }
+        }
+    }
+}
+
+/// The ids of the questions answered in `answers`, nested ones included.
+fn answered_ids(questions: &[Question], answers: &AnswerSet, ids: &mut BTreeSet<i32>) {
+    for (id, answer) in answers.iter() {
+        ids.insert(id);
+        let question = match questions.iter().find(|q| q.get_id() == id) {
+            Some(question) => question,
+            None => continue,
+        };
+        answered_in(question, answer, ids);
+    }
+}
+
+fn answered_in(question: &Question, answer: &Answer, ids: &mut BTreeSet<i32>) {
+    match (question, answer) {
+        (Question::Group(group), Answer::Group(answers)) => {
+            answered_ids(&group.questions, answers, ids)
+        }
+        (Question::ArrayOf(array), Answer::ArrayOf(items)) => {
+            for item in items {
+                ids.insert(array.question.get_id());
+                answered_in(&array.question, item, ids);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_rule(from: &QuestionList, to: &QuestionList, rule: &Rule) -> Result<(), String> {
+    match rule {
+        Rule::RenameItem {
+            question_id,
+            from: item,
+            to: renamed,
+        } => {
+            match find(&from.questions, *question_id) {
+                Some(Question::FixedList(list)) if list.items.contains(item) => {}
+                Some(Question::FixedList(_)) => {
+                    return Err(format!("question {} has no item {}", question_id, item))
+                }
+                _ => return Err(format!("question {} is not a FixedList", question_id)),
+            }
+            match find(&to.questions, *question_id) {
+                Some(Question::FixedList(list)) if !list.items.contains(renamed) => Err(format!(
+                    "question {} has no item {} in the later revision",
+                    question_id, renamed
+                )),
+                _ => Ok(()),
+            }
+        }
+        Rule::DropQuestion { question_id } => match find(&from.questions, *question_id) {
+            Some(_) => Ok(()),
+            None => Err(format!("question {} does not exist", question_id)),
+        },
+        Rule::Fill { question_id, value } => {
+            let question = match to.questions.iter().find(|q| q.get_id() == *question_id) {
+                Some(question) => question,
+                None => {
+                    return Err(format!(
+                        "question {} is not a top-level question of the later revision",
+                        question_id
+                    ))
+                }
+            };
+            let mut answers = Map::new();
+            answers.insert(question.property_name(), value.clone());
+            answer::parse(to, &Value::Object(answers))
+                .map(|_| ())
+                .map_err(|errors| {
+                    errors
+                        .iter()
+                        .map(|error| format!("value {}", error.message))
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qna::questionlist::question;
+    use serde_json::json;
+
+    /// The sample with the `bork` item renamed, the range of question 88
+    /// narrowed, question 222 and the conditional question 3 removed, and a
+    /// new required question 40.
+    fn revised() -> QuestionList {
+        let mut revised = questionlist::sample_string_questions();
+        revised.version = 2;
+        revised
+            .questions
+            .retain(|q| q.get_id() != 222 && q.get_id() != 3);
+        for question in &mut revised.questions {
+            match question {
+                Question::FixedList(list) if list.id == 24 => {
+                    list.items = vec!["zork".into(), "borked".into()];
+                }
+                Question::Integer(integer) => integer.max = Some(50),
+                _ => {}
+            }
+        }
+        revised
+            .questions
+            .push(Question::TrueOrFalse(question::TrueOrFalse {
+                id: 40,
+                key: Some("newsletter".into()),
+                title: "Send me the newsletter".into(),
+                description: "".into(),
+                default: None,
+                required: true,
+                visible_if: None,
+                ui: None,
+            }));
+        revised
+    }
+
+    fn submitted() -> Value {
+        json!({
+            "1": "Ice",
+            "8": ["Ice", "Cream"],
+            "88": 40,
+            "222": true,
+            "24": "bork",
+            "3": "Some text",
+            "899": true,
+            "dependants": [{ "name": "Ann", "age": 7 }],
+        })
+    }
+
+    #[test]
+    fn lists_changes_between_revisions() {
+        let sample = questionlist::sample_string_questions();
+        let changes = diff(&sample, &revised());
+        assert_eq!(
+            changes,
+            vec![
+                Change::RangeNarrowed {
+                    question_id: 88,
+                    min: None,
+                    max: Some("50".into()),
+                },
+                Change::QuestionRemoved { question_id: 222 },
+                Change::ItemsRemoved {
+                    question_id: 24,
+                    items: vec!["bork".into()],
+                },
+                Change::ItemsAdded {
+                    question_id: 24,
+                    items: vec!["borked".into()],
+                },
+                Change::QuestionRemoved { question_id: 3 },
+                Change::QuestionAdded {
+                    question_id: 40,
+                    required: true,
+                },
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&changes[0]).unwrap(),
+            json!({ "change": "range-narrowed", "questionId": 88, "min": null, "max": "50" })
+        );
+        assert!(diff(&sample, &sample).is_empty());
+    }
+
+    #[test]
+    fn migrates_answers_by_the_rules() {
+        let (sample, revised) = (questionlist::sample_string_questions(), revised());
+        let rules: Vec<Rule> = serde_json::from_value(json!([
+            { "rule": "rename-item", "questionId": 24, "from": "bork", "to": "borked" },
+            { "rule": "drop-question", "questionId": 222 },
+            { "rule": "drop-question", "questionId": 3 },
+            { "rule": "fill", "questionId": 40, "value": false },
+        ]))
+        .unwrap();
+        let plan = Plan::new(&sample, &revised, rules.clone()).unwrap();
+
+        let migrated = plan.migrate(&submitted(), Utc::now()).unwrap();
+        assert_eq!(migrated["24"], "borked");
+        assert_eq!(migrated["newsletter"], false);
+        assert!(migrated.get("222").is_none());
+        assert_eq!(migrated["dependants"], json!([{ "name": "Ann", "age": 7 }]));
+
+        let mut answers = submitted();
+        answers["88"] = json!(70);
+        match plan.migrate(&answers, Utc::now()) {
+            Err(Failure::Invalid { errors }) => assert_eq!(errors[0].pointer, "/88"),
+            other => panic!("expected an invalid migration, got {:?}", other),
+        }
+
+        let plan = Plan::new(&sample, &revised, rules[..1].to_vec()).unwrap();
+        assert_eq!(
+            plan.migrate(&submitted(), Utc::now()),
+            Err(Failure::Undropped {
+                question_ids: vec![3, 222]
+            })
+        );
+        assert!(matches!(
+            plan.migrate(&json!({ "88": "ten" }), Utc::now()),
+            Err(Failure::Unreadable { .. })
+        ));
+    }
+
+    #[test]
+    fn fails_answers_to_questions_that_move() {
+        let sample = questionlist::sample_string_questions();
+        let mut revised = sample.clone();
+        revised.version = 2;
+        let moved = revised.questions.remove(0);
+        if let Question::ArrayOf(array) = &mut revised.questions[0] {
+            array.required = false;
+            array.question = Question::Group(question::Group {
+                id: 80,
+                key: None,
+                title: "Flavour".into(),
+                description: "".into(),
+                required: true,
+                questions: vec![moved, array.question.clone()],
+                visible_if: None,
+                ui: None,
+            });
+        }
+        assert_eq!(
+            diff(&sample, &revised),
+            vec![
+                Change::ParentChanged {
+                    question_id: 1,
+                    from: None,
+                    to: Some(80),
+                },
+                Change::ParentChanged {
+                    question_id: 81,
+                    from: Some(8),
+                    to: Some(80),
+                },
+                Change::QuestionAdded {
+                    question_id: 80,
+                    required: true,
+                },
+            ]
+        );
+
+        let plan = Plan::new(&sample, &revised, vec![]).unwrap();
+        assert_eq!(
+            plan.migrate(&submitted(), Utc::now()),
+            Err(Failure::Undropped {
+                question_ids: vec![1, 81]
+            })
+        );
+        let rules = serde_json::from_value(json!([
+            { "rule": "drop-question", "questionId": 1 },
+            { "rule": "drop-question", "questionId": 8 },
+        ]))
+        .unwrap();
+        let plan = Plan::new(&sample, &revised, rules).unwrap();
+        let migrated = plan.migrate(&submitted(), Utc::now()).unwrap();
+        assert!(migrated.get("1").is_none());
+        assert!(migrated.get("8").is_none());
+    }
+
+    #[test]
+    fn rejects_rules_that_do_not_apply() {
+        let (sample, revised) = (questionlist::sample_string_questions(), revised());
+        let rules: Vec<Rule> = serde_json::from_value(json!([
+            { "rule": "rename-item", "questionId": 24, "from": "bork", "to": "zorked" },
+            { "rule": "rename-item", "questionId": 88, "from": "1", "to": "2" },
+            { "rule": "drop-question", "questionId": 404 },
+            { "rule": "fill", "questionId": 40, "value": "yes" },
+            { "rule": "fill", "questionId": 33, "value": 1 },
+        ]))
+        .unwrap();
+
+        let errors = Plan::new(&sample, &revised, rules).unwrap_err();
+        assert_eq!(
+            errors.iter().map(|error| error.rule).collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4]
+        );
+        assert_eq!(errors[3].message, "value must be of type boolean");
+        assert!(serde_json::from_value::<Rule>(
+            json!({ "rule": "drop-question", "questionId": 3, "also": 1 })
+        )
+        .is_err());
+    }
+}
@@ -1,3 +1,10 @@
-// pub mod answerdto;
+pub mod answer;
+pub mod dates;
+pub mod lint;
+pub mod migration;
 pub mod questiondto;
 pub mod questionlist;
+pub mod schemacache;
+pub mod schemaimport;
+pub mod validation;
+pub mod validator;
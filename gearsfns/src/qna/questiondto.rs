@@ -1,18 +1,32 @@
 use std::collections::HashMap;
+use std::convert::{TryFrom, TryInto};
 
+use indexmap::IndexMap;
+use rust_decimal::Decimal;
+use serde_json::{json, Map, Value};
+
+use super::dates::TIME_PATTERN;
 use super::questionlist;
+use super::questionlist::question::{SelectionMode, TextFormat, MAX_PRECISION};
+use crate::Error;
 
 #[derive(Debug, Serialize, Clone, PartialEq)]
 #[serde(tag = "type")]
 pub enum Node {
     #[serde(rename = "integer")]
     Integer(IntegerNode),
+    #[serde(rename = "number")]
+    Number(NumberNode),
     #[serde(rename = "string")]
     String(StringNode),
     #[serde(rename = "boolean")]
     Boolean(BooleanNode),
     #[serde(rename = "string")]
     FixedList(FixedListNode),
+    #[serde(rename = "array")]
+    MultiSelect(MultiSelectNode),
+    #[serde(rename = "string")]
+    Enum(EnumNode),
     #[serde(rename = "object")]
     Object(ObjectNode),
     #[serde(rename = "array")]
@@ -24,17 +38,82 @@ pub enum Node {
 pub struct ObjectNode {
     pub title: String,
     pub description: String,
-    pub properties: HashMap<String, Node>,
+    /// In the order the questions were authored, which is the order forms
+    /// render them in.
+    pub properties: IndexMap<String, Node>,
     pub additional_properties: bool,
     pub required: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub all_of: Vec<Conditional>,
 }
 
+/// Draft-7 `if`/`then`/`else` that only admits an answer to a question while
+/// its visibility condition holds, and only then requires it.
 #[derive(Debug, Serialize, Clone, PartialEq)]
-#[serde(deny_unknown_fields)]
+pub struct Conditional {
+    #[serde(rename = "if")]
+    pub condition: Clause,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub then: Option<Clause>,
+    #[serde(rename = "else")]
+    pub otherwise: Clause,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Default)]
+pub struct Clause {
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub properties: HashMap<String, Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub required: Vec<String>,
+}
+
+impl Conditional {
+    /// `on` is the property name of the question `condition` refers to.
+    fn new(
+        question: &questionlist::Question,
+        condition: &questionlist::question::Condition,
+        on: &str,
+    ) -> Self {
+        let id = question.property_name();
+        let on = on.to_string();
+
+        let mut when = Clause::default();
+        when.properties
+            .insert(on.clone(), json!({ "const": condition.equals }));
+        when.required.push(on);
+
+        let mut hidden = Clause::default();
+        hidden.properties.insert(id.clone(), Value::Bool(false));
+
+        Conditional {
+            condition: when,
+            then: if question.is_required() {
+                Some(Clause {
+                    required: vec![id],
+                    ..Default::default()
+                })
+            } else {
+                None
+            },
+            otherwise: hidden,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub struct StringNode {
     pub title: String,
     pub description: String,
     pub default: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<TextFormat>,
 }
 #[derive(Debug, Serialize, Clone, PartialEq)]
 pub struct BooleanNode {
@@ -57,11 +136,48 @@ pub struct IntegerNode {
     pub maximum: Option<i32>,
 }
 
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NumberNode {
+    pub title: String,
+    pub description: String,
+    pub default: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multiple_of: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<Decimal>,
+}
+
 #[derive(Debug, Serialize, Clone, PartialEq)]
 pub struct FixedListNode {
+    pub title: String,
+    pub description: String,
+    pub default: Option<String>,
+    #[serde(rename = "enum")]
+    pub items: Vec<String>,
+    #[serde(rename = "enumNames")]
+    pub item_names: Vec<String>,
+}
+
+/// A set of distinct items picked from a fixed list.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiSelectNode {
     pub title: String,
     pub description: String,
     pub default: Vec<String>,
+    pub items: Box<Node>,
+    pub unique_items: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_items: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct EnumNode {
     #[serde(rename = "enum")]
     pub items: Vec<String>,
     #[serde(rename = "enumNames")]
@@ -69,40 +185,178 @@ pub struct FixedListNode {
 }
 
 #[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct ArrayNode {
     pub title: String,
     pub description: String,
     pub items: Box<Node>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_items: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<i32>,
+}
+
+/// Everything a react-jsonschema-form frontend needs to render a
+/// questionnaire.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FormBundle {
+    pub schema: Node,
+    pub ui_schema: ui_schema::UiNode,
+    pub form_data: Map<String, Value>,
 }
 
-impl From<questionlist::QuestionList> for ObjectNode {
-    fn from(question_list: questionlist::QuestionList) -> Self {
+impl TryFrom<questionlist::QuestionList> for FormBundle {
+    type Error = Error;
+
+    fn try_from(question_list: questionlist::QuestionList) -> Result<Self, Error> {
+        Ok(FormBundle {
+            schema: Node::Object(question_list.clone().try_into()?),
+            form_data: form_data(&question_list),
+            ui_schema: question_list.into(),
+        })
+    }
+}
+
+/// Initial form data, holding the default of every question that has one
+/// and is visible given the other defaults.
+pub fn form_data(question_list: &questionlist::QuestionList) -> Map<String, Value> {
+    let defaults = defaults(&question_list.questions);
+    question_list
+        .questions
+        .iter()
+        .filter(|q| match q.visible_if() {
+            Some(condition) => condition.holds(defaults.get(&questionlist::property_name(
+                &question_list.questions,
+                condition.question_id,
+            ))),
+            None => true,
+        })
+        .filter_map(|q| {
+            let name = q.property_name();
+            defaults.get(&name).map(|value| (name, value.clone()))
+        })
+        .collect()
+}
+
+fn defaults(questions: &[questionlist::Question]) -> Map<String, Value> {
+    questions
+        .iter()
+        .filter_map(|q| default_value(q).map(|value| (q.property_name(), value)))
+        .collect()
+}
+
+fn default_value(question: &questionlist::Question) -> Option<Value> {
+    match question {
+        questionlist::Question::Integer(q) => q.default.map(Value::from),
+        questionlist::Question::Number(q) => q.default.and_then(|d| serde_json::to_value(d).ok()),
+        questionlist::Question::FreeText(q) => q.default.clone().map(Value::from),
+        questionlist::Question::TrueOrFalse(q) => q.default.map(Value::from),
+        questionlist::Question::FixedList(q) => match q.mode {
+            SelectionMode::Single => q.default.first().cloned().map(Value::from),
+            SelectionMode::Multiple if q.default.is_empty() => None,
+            SelectionMode::Multiple => Some(q.default.clone().into()),
+        },
+        questionlist::Question::ArrayOf(_) => None,
+        questionlist::Question::Group(q) => {
+            let defaults = defaults(&q.questions);
+            if defaults.is_empty() {
+                None
+            } else {
+                Some(defaults.into())
+            }
+        }
+        questionlist::Question::Date(q) => q.default.map(|d| d.to_string().into()),
+        questionlist::Question::Time(q) => q.default.map(|t| t.to_string().into()),
+        questionlist::Question::DateTime(q) => q.default.map(|dt| dt.to_rfc3339().into()),
+    }
+}
+
+/// Fails on questionnaires the linter rejects but that would otherwise
+/// convert silently into a wrong schema.
+impl TryFrom<questionlist::QuestionList> for ObjectNode {
+    type Error = Error;
+
+    fn try_from(question_list: questionlist::QuestionList) -> Result<Self, Error> {
         let questions = question_list.questions;
 
         let req: Vec<String> = questions
             .iter()
-            .filter(|e| e.is_required())
-            .map(|e| e.get_id().to_string())
+            .filter(|e| e.is_required() && e.visible_if().is_none())
+            .map(|e| e.property_name())
             .collect();
 
-        let props: HashMap<String, Node> = questions
-            .iter()
-            .map(|q| (q.get_id().to_string(), q.clone().into()))
-            .collect();
+        let mut conditionals = vec![];
+        for q in &questions {
+            if let Some(condition) = q.visible_if() {
+                if !questions
+                    .iter()
+                    .any(|on| on.get_id() == condition.question_id)
+                {
+                    return Err(Error::Conversion(format!(
+                        "question {} depends on unknown question {}",
+                        q.get_id(),
+                        condition.question_id
+                    )));
+                }
+                let on = questionlist::property_name(&questions, condition.question_id);
+                conditionals.push(Conditional::new(q, condition, &on));
+            }
+        }
 
-        ObjectNode {
+        Ok(ObjectNode {
+            properties: properties(&questions)?,
             title: question_list.title,
             description: question_list.description,
-            properties: props,
             additional_properties: false,
             required: req,
+            all_of: conditionals,
+        })
+    }
+}
+
+/// A node per question, failing if two of them share a property name.
+fn properties(questions: &[questionlist::Question]) -> Result<IndexMap<String, Node>, Error> {
+    let mut properties = IndexMap::new();
+    for question in questions {
+        let name = question.property_name();
+        if properties
+            .insert(name.clone(), question.clone().try_into()?)
+            .is_some()
+        {
+            return Err(Error::Conversion(format!(
+                "property \"{}\" is used by more than one question",
+                name
+            )));
         }
     }
+    Ok(properties)
 }
 
-impl From<questionlist::Question> for Node {
-    fn from(question: questionlist::Question) -> Self {
-        match question {
+impl TryFrom<questionlist::Question> for Node {
+    type Error = Error;
+
+    fn try_from(question: questionlist::Question) -> Result<Self, Error> {
+        match &question {
+            questionlist::Question::FixedList(q) if q.items.len() != q.item_names.len() => {
+                return Err(Error::Conversion(format!(
+                    "question {} has {} item names for {} items",
+                    q.id,
+                    q.item_names.len(),
+                    q.items.len()
+                )))
+            }
+            questionlist::Question::Number(q)
+                if q.precision.is_some_and(|places| places > MAX_PRECISION) =>
+            {
+                return Err(Error::Conversion(format!(
+                    "question {} asks for more than {} decimal places",
+                    q.id, MAX_PRECISION
+                )))
+            }
+            _ => {}
+        }
+        Ok(match question {
             questionlist::Question::Integer(q) => Node::Integer(IntegerNode {
                 title: q.title,
                 description: q.description,
@@ -111,63 +365,203 @@ impl From<questionlist::Question> for Node {
                 minimum: q.min,
                 maximum: q.max,
             }),
-            questionlist::Question::FreeText(q) => Node::String(StringNode {
+            questionlist::Question::Number(q) => Node::Number(NumberNode {
+                multiple_of: q.multiple_of(),
                 title: q.title,
                 description: q.description,
                 default: q.default,
+                minimum: q.min,
+                maximum: q.max,
             }),
-            questionlist::Question::TrueOrFalse(q) => Node::Boolean(BooleanNode {
+            questionlist::Question::FreeText(q) => Node::String(StringNode {
                 title: q.title,
                 description: q.description,
                 default: q.default,
+                min_length: q.min_length,
+                max_length: q.max_length,
+                pattern: q.pattern,
+                format: q.format,
             }),
-            questionlist::Question::FixedList(q) => Node::FixedList(FixedListNode {
+            questionlist::Question::TrueOrFalse(q) => Node::Boolean(BooleanNode {
                 title: q.title,
                 description: q.description,
                 default: q.default,
-                items: q.items,
-                item_names: q.item_names,
             }),
+            questionlist::Question::FixedList(q) => match q.mode {
+                SelectionMode::Single => Node::FixedList(FixedListNode {
+                    title: q.title,
+                    description: q.description,
+                    default: q.default.into_iter().next(),
+                    items: q.items,
+                    item_names: q.item_names,
+                }),
+                SelectionMode::Multiple => Node::MultiSelect(MultiSelectNode {
+                    title: q.title,
+                    description: q.description,
+                    default: q.default,
+                    items: Box::new(Node::Enum(EnumNode {
+                        items: q.items,
+                        item_names: q.item_names,
+                    })),
+                    unique_items: true,
+                    min_items: q.min_selections,
+                    max_items: q.max_selections,
+                }),
+            },
             questionlist::Question::ArrayOf(q) => {
                 let arr = q.as_ref().clone();
                 Node::Array(ArrayNode {
                     title: arr.title,
                     description: arr.description,
-                    items: Box::new(arr.question.into()),
+                    items: Box::new(arr.question.try_into()?),
+                    min_items: arr.min_items,
+                    max_items: arr.max_items,
                 })
             }
-        }
+            // Members of a group are never conditional, so each required
+            // one is required whenever the group is answered.
+            questionlist::Question::Group(q) => Node::Object(ObjectNode {
+                required: q
+                    .questions
+                    .iter()
+                    .filter(|q| q.is_required())
+                    .map(|q| q.property_name())
+                    .collect(),
+                properties: properties(&q.questions)?,
+                title: q.title,
+                description: q.description,
+                additional_properties: false,
+                all_of: vec![],
+            }),
+            // Date bounds may be relative to today, so they are only
+            // enforced natively and the schema stays the same every day.
+            questionlist::Question::Date(q) => Node::String(StringNode {
+                title: q.title,
+                description: q.description,
+                default: q.default.map(|d| d.to_string()),
+                min_length: None,
+                max_length: None,
+                pattern: None,
+                format: Some(TextFormat::Date),
+            }),
+            questionlist::Question::Time(q) => Node::String(StringNode {
+                title: q.title,
+                description: q.description,
+                default: q.default.map(|t| t.to_string()),
+                min_length: None,
+                max_length: None,
+                pattern: Some(TIME_PATTERN.into()),
+                format: None,
+            }),
+            questionlist::Question::DateTime(q) => Node::String(StringNode {
+                title: q.title,
+                description: q.description,
+                default: q.default.map(|dt| dt.to_rfc3339()),
+                min_length: None,
+                max_length: None,
+                pattern: None,
+                format: Some(TextFormat::DateTime),
+            }),
+        })
     }
 }
 
 pub mod ui_schema {
     use super::super::questionlist;
-    use super::super::questionlist::question::ui;
+    use super::super::questionlist::question::{ui, SelectionMode};
 
-    use std::collections::HashMap;
+    use indexmap::IndexMap;
+    use serde_json::{json, Map, Value};
 
-    pub type UiObject = HashMap<String, UiNode>;
+    pub type UiObject = IndexMap<String, UiNode>;
 
     #[derive(Debug, Serialize, Clone, PartialEq)]
     pub struct UiNode {
         #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "ui:placeholder")]
         placeholder: Option<String>,
 
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "ui:help")]
+        ui_help: Option<String>,
+
         #[serde(skip_serializing_if = "Option::is_none")]
         #[serde(rename = "ui:widget")]
         ui_widget: Option<String>,
 
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "ui:autofocus")]
+        ui_autofocus: Option<bool>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "ui:readonly")]
+        ui_readonly: Option<bool>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "ui:disabled")]
+        ui_disabled: Option<bool>,
+
+        /// Items of a fixed list that are shown but cannot be picked.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "ui:enumDisabled")]
+        ui_enum_disabled: Option<Vec<String>>,
+
         #[serde(skip_serializing_if = "Option::is_none")]
         #[serde(rename = "ui:options")]
-        ui_options: Option<HashMap<String, String>>,
+        ui_options: Option<Map<String, Value>>,
+
+        /// Question ids in author order, for the questionnaire and groups.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "ui:order")]
+        ui_order: Option<Vec<String>>,
+
+        /// Nodes for the members of a group, or `items` of an array.
+        #[serde(flatten)]
+        children: UiObject,
     }
 
     impl UiNode {
         pub fn empty() -> Self {
             Self {
                 placeholder: None,
-                ui_options: None,
+                ui_help: None,
                 ui_widget: None,
+                ui_autofocus: None,
+                ui_readonly: None,
+                ui_disabled: None,
+                ui_enum_disabled: None,
+                ui_options: None,
+                ui_order: None,
+                children: IndexMap::new(),
+            }
+        }
+
+        /// Adds an entry to `ui:options`.
+        fn option(&mut self, name: &str, value: Value) {
+            self.ui_options
+                .get_or_insert_with(Map::new)
+                .insert(name.into(), value);
+        }
+    }
+
+    /// Flags are only emitted when set, leaving frontend defaults alone.
+    fn flag(set: bool) -> Option<bool> {
+        if set {
+            Some(true)
+        } else {
+            None
+        }
+    }
+
+    impl From<ui::Field> for UiNode {
+        fn from(ui: ui::Field) -> Self {
+            UiNode {
+                placeholder: ui.placeholder,
+                ui_help: ui.help,
+                ui_autofocus: flag(ui.autofocus),
+                ui_readonly: flag(ui.readonly),
+                ui_disabled: flag(ui.disabled),
+                ..UiNode::empty()
             }
         }
     }
@@ -175,46 +569,266 @@ pub mod ui_schema {
     impl From<ui::TrueOrFalse> for UiNode {
         fn from(ui: ui::TrueOrFalse) -> Self {
             UiNode {
-                placeholder: None,
-                ui_options: None,
-                ui_widget: if let Some(widget) = ui.widget {
-                    Some(widget.to_string())
-                } else {
-                    None
-                },
+                ui_widget: ui.widget.as_ref().map(ToString::to_string),
+                ..ui.field.into()
+            }
+        }
+    }
+
+    impl From<ui::Integer> for UiNode {
+        fn from(ui: ui::Integer) -> Self {
+            UiNode {
+                ui_widget: ui.widget.as_ref().map(ToString::to_string),
+                ..ui.field.into()
+            }
+        }
+    }
+
+    impl From<ui::Number> for UiNode {
+        fn from(ui: ui::Number) -> Self {
+            UiNode {
+                ui_widget: ui.widget.as_ref().map(ToString::to_string),
+                ..ui.field.into()
             }
         }
     }
-    impl From<questionlist::QuestionList> for UiObject {
+
+    impl From<ui::FreeText> for UiNode {
+        fn from(ui: ui::FreeText) -> Self {
+            let mut node = UiNode {
+                ui_widget: ui.widget.as_ref().map(ToString::to_string),
+                ..ui.field.into()
+            };
+            if let Some(rows) = ui.rows {
+                node.option("rows", rows.into());
+            }
+            if let Some(input_type) = ui.input_type {
+                node.option("inputType", input_type.to_string().into());
+            }
+            node
+        }
+    }
+
+    impl From<ui::FixedList> for UiNode {
+        fn from(ui: ui::FixedList) -> Self {
+            UiNode {
+                ui_widget: ui.widget.as_ref().map(ToString::to_string),
+                ui_enum_disabled: Some(ui.enum_disabled.clone()).filter(|items| !items.is_empty()),
+                ..ui.field.into()
+            }
+        }
+    }
+
+    impl From<ui::ArrayOf> for UiNode {
+        fn from(ui: ui::ArrayOf) -> Self {
+            let mut node = UiNode::from(ui.field);
+            if let Some(orderable) = ui.orderable {
+                node.option("orderable", orderable.into());
+            }
+            node
+        }
+    }
+
+    impl From<questionlist::QuestionList> for UiNode {
         fn from(question_list: questionlist::QuestionList) -> Self {
-            question_list
-                .questions
+            members(&question_list.questions, UiNode::empty())
+        }
+    }
+
+    /// Adds `questions` to `node` in order, with a child node for each.
+    fn members(questions: &[questionlist::Question], node: UiNode) -> UiNode {
+        UiNode {
+            ui_order: Some(questions.iter().map(|q| q.property_name()).collect()),
+            children: questions
                 .iter()
-                .map(|q| (q.get_id().to_string(), q.clone().into()))
-                .collect()
+                .map(|q| {
+                    let mut node = UiNode::from(q.clone());
+                    // Frontends hide the field until the condition holds; the
+                    // schema's `allOf` rejects answers given while it is hidden.
+                    if let Some(condition) = q.visible_if() {
+                        node.option(
+                            "visibleIf",
+                            json!({
+                                "questionId": condition.question_id,
+                                "property": questionlist::property_name(
+                                    questions,
+                                    condition.question_id,
+                                ),
+                                "equals": condition.equals,
+                            }),
+                        );
+                    }
+                    (q.property_name(), node)
+                })
+                .collect(),
+            ..node
         }
     }
 
+    /// The node for a question's own `ui` options, if it has any.
+    fn options<T: Clone + Into<UiNode>>(ui: &Option<T>) -> UiNode {
+        ui.clone().map_or_else(UiNode::empty, Into::into)
+    }
+
     impl From<questionlist::Question> for UiNode {
         fn from(question: questionlist::Question) -> Self {
-            match question {
-                questionlist::Question::TrueOrFalse(q) => {
-                    if let Some(ui) = q.ui {
-                        UiNode {
-                            placeholder: None,
-                            ui_options: None,
-                            ui_widget: if let Some(widget) = ui.widget {
-                                Some(widget.to_string())
-                            } else {
-                                None
-                            },
-                        }
-                    } else {
-                        UiNode::empty()
+            match &question {
+                questionlist::Question::Integer(q) => options(&q.ui),
+                questionlist::Question::TrueOrFalse(q) => options(&q.ui),
+                questionlist::Question::FreeText(q) => options(&q.ui),
+                questionlist::Question::FixedList(q) => {
+                    let node = options(&q.ui);
+                    match q.mode {
+                        SelectionMode::Multiple => UiNode {
+                            ui_widget: Some("checkboxes".into()),
+                            ..node
+                        },
+                        SelectionMode::Single => node,
                     }
                 }
-                _ => UiNode::empty(),
+                questionlist::Question::Number(q) => {
+                    let mut node = options(&q.ui);
+                    if let Some(unit) = &q.unit {
+                        node.option("unit", unit.clone().into());
+                    }
+                    node
+                }
+                questionlist::Question::ArrayOf(q) => UiNode {
+                    children: vec![("items".to_string(), q.question.clone().into())]
+                        .into_iter()
+                        .collect(),
+                    ..options(&q.ui)
+                },
+                questionlist::Question::Group(q) => members(&q.questions, options(&q.ui)),
+                questionlist::Question::Date(q) => UiNode {
+                    ui_widget: Some("date".into()),
+                    ..options(&q.ui)
+                },
+                questionlist::Question::Time(q) => UiNode {
+                    ui_widget: Some("time".into()),
+                    ..options(&q.ui)
+                },
+                questionlist::Question::DateTime(q) => UiNode {
+                    ui_widget: Some("datetime".into()),
+                    ..options(&q.ui)
+                },
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_author_order() {
+        let sample = questionlist::sample_string_questions();
+        let ids: Vec<String> = sample.questions.iter().map(|q| q.property_name()).collect();
+
+        let bundle = serde_json::to_value(FormBundle::try_from(sample).unwrap()).unwrap();
+        let properties: Vec<&String> = bundle["schema"]["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect();
+        assert_eq!(properties, ids.iter().collect::<Vec<_>>());
+        assert_eq!(bundle["uiSchema"]["ui:order"], json!(ids));
+        assert_eq!(
+            bundle["uiSchema"]["dependants"]["items"]["ui:order"],
+            json!(["name", "age", "relationship"])
+        );
+    }
+
+    #[test]
+    fn emits_ui_options() {
+        let question_list: questionlist::QuestionList = serde_json::from_value(json!({
+            "title": "Order",
+            "description": "",
+            "questions": [
+                { "Integer": { "id": 1, "title": "Quantity", "description": "", "required": true,
+                    "visible_if": null, "default": null, "step": null, "min": 1, "max": 5,
+                    "ui": { "widget": "range", "autofocus": true } } },
+                { "FreeText": { "id": 2, "title": "Phone", "description": "", "default": null,
+                    "required": false, "visible_if": null, "min_length": null, "max_length": null,
+                    "pattern": null, "format": null,
+                    "ui": { "input_type": "tel", "placeholder": "0400 000 000" } } },
+                { "FixedList": { "id": 3, "title": "Size", "description": "", "required": true,
+                    "visible_if": null, "default": [], "items": ["s", "m"], "item_names": ["S", "M"],
+                    "min_selections": null, "max_selections": null,
+                    "ui": { "widget": "select", "enum_disabled": ["s"], "readonly": true } } },
+                { "ArrayOf": { "id": 4, "title": "Notes", "description": "", "required": false,
+                    "visible_if": null, "min_items": null, "max_items": null,
+                    "ui": { "orderable": false, "help": "One per line" },
+                    "question": { "Date": { "id": 5, "title": "On", "description": "",
+                        "required": true, "visible_if": null, "default": null, "min": null,
+                        "max": null, "timezone": null, "ui": { "disabled": true } } } } },
+            ],
+        }))
+        .unwrap();
+
+        let ui_schema = serde_json::to_value(ui_schema::UiNode::from(question_list)).unwrap();
+        assert_eq!(
+            ui_schema["1"],
+            json!({ "ui:widget": "range", "ui:autofocus": true })
+        );
+        assert_eq!(
+            ui_schema["2"],
+            json!({ "ui:placeholder": "0400 000 000", "ui:options": { "inputType": "tel" } })
+        );
+        assert_eq!(
+            ui_schema["3"],
+            json!({ "ui:widget": "select", "ui:readonly": true, "ui:enumDisabled": ["s"] })
+        );
+        assert_eq!(
+            ui_schema["4"],
+            json!({
+                "ui:help": "One per line",
+                "ui:options": { "orderable": false },
+                "items": { "ui:widget": "date", "ui:disabled": true },
+            })
+        );
+
+        // Options shared by every type are read alongside the type's own,
+        // and anything else is still refused.
+        let ui: Result<questionlist::question::ui::Integer, _> = serde_json::from_value(
+            json!({ "widget": "range", "autofocus": true, "colour": "red" }),
+        );
+        assert!(ui.is_err());
+    }
+
+    #[test]
+    fn rejects_broken_questionnaires() {
+        let mut sample = questionlist::sample_string_questions();
+        if let questionlist::Question::FreeText(q) = &mut sample.questions[0] {
+            q.key = Some("dependants".into());
+        }
+        assert!(matches!(
+            FormBundle::try_from(sample),
+            Err(Error::Conversion(message)) if message.contains("\"dependants\"")
+        ));
+
+        let mut sample = questionlist::sample_string_questions();
+        if let questionlist::Question::FixedList(q) = &mut sample.questions[4] {
+            q.item_names.pop();
+        }
+        assert!(matches!(
+            ObjectNode::try_from(sample),
+            Err(Error::Conversion(_))
+        ));
+    }
+
+    #[test]
+    fn sample_matches_snapshot() {
+        let render = || {
+            let bundle = FormBundle::try_from(questionlist::sample_string_questions()).unwrap();
+            serde_json::to_string_pretty(&bundle).unwrap()
+        };
+        let rendered = render();
+        assert_eq!(rendered, render());
+        assert_eq!(
+            rendered,
+            include_str!("snapshots/sample_form_bundle.json").trim_end()
+        );
+    }
+}
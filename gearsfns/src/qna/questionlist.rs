@@ -3,6 +3,8 @@
 pub struct QuestionList {
     pub title: String,
     pub description: String,
+    #[serde(default)]
+    pub version: u32,
     pub questions: Vec<Question>,
 }
 
@@ -10,55 +12,242 @@ pub struct QuestionList {
 #[serde(deny_unknown_fields)]
 pub enum Question {
     Integer(question::Integer),
+    Number(question::Number),
     FreeText(question::FreeText),
     TrueOrFalse(question::TrueOrFalse),
     FixedList(question::FixedList),
     ArrayOf(Box<question::ArrayOf>),
+    Group(question::Group),
+    Date(question::Date),
+    Time(question::Time),
+    DateTime(question::DateTime),
+}
+
+/// The property name of the question with `id` among `questions`, which is
+/// how conditions find the answer they depend on.
+pub fn property_name(questions: &[Question], id: i32) -> String {
+    questions
+        .iter()
+        .find(|q| q.get_id() == id)
+        .map_or_else(|| id.to_string(), Question::property_name)
+}
+
+/// Where the answer to one question is found in a submission.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyMapping {
+    pub question_id: i32,
+    pub property: String,
+    /// The group or list the question is nested in, if any.
+    pub parent_id: Option<i32>,
+}
+
+impl QuestionList {
+    /// The property name of every question, nested ones included, so stored
+    /// answers can be related back to question ids.
+    pub fn key_mappings(&self) -> Vec<KeyMapping> {
+        let mut mappings = vec![];
+        add_mappings(&self.questions, None, &mut mappings);
+        mappings
+    }
+}
+
+fn add_mappings(questions: &[Question], parent_id: Option<i32>, mappings: &mut Vec<KeyMapping>) {
+    for question in questions {
+        mappings.push(KeyMapping {
+            question_id: question.get_id(),
+            property: question.property_name(),
+            parent_id,
+        });
+        match question {
+            Question::ArrayOf(array) => add_mappings(
+                std::slice::from_ref(&array.question),
+                Some(array.id),
+                mappings,
+            ),
+            Question::Group(group) => add_mappings(&group.questions, Some(group.id), mappings),
+            _ => {}
+        }
+    }
 }
 
 impl Question {
+    /// The externally tagged variant name, as it appears in documents.
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Question::Integer(_) => "Integer",
+            Question::Number(_) => "Number",
+            Question::FreeText(_) => "FreeText",
+            Question::TrueOrFalse(_) => "TrueOrFalse",
+            Question::FixedList(_) => "FixedList",
+            Question::ArrayOf(_) => "ArrayOf",
+            Question::Group(_) => "Group",
+            Question::Date(_) => "Date",
+            Question::Time(_) => "Time",
+            Question::DateTime(_) => "DateTime",
+        }
+    }
     pub fn get_id(&self) -> i32 {
         match self {
             Question::Integer(q) => q.id,
+            Question::Number(q) => q.id,
             Question::FreeText(q) => q.id,
             Question::TrueOrFalse(q) => q.id,
             Question::FixedList(q) => q.id,
             Question::ArrayOf(q) => q.id,
+            Question::Group(q) => q.id,
+            Question::Date(q) => q.id,
+            Question::Time(q) => q.id,
+            Question::DateTime(q) => q.id,
+        }
+    }
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            Question::Integer(q) => q.key.as_deref(),
+            Question::Number(q) => q.key.as_deref(),
+            Question::FreeText(q) => q.key.as_deref(),
+            Question::TrueOrFalse(q) => q.key.as_deref(),
+            Question::FixedList(q) => q.key.as_deref(),
+            Question::ArrayOf(q) => q.key.as_deref(),
+            Question::Group(q) => q.key.as_deref(),
+            Question::Date(q) => q.key.as_deref(),
+            Question::Time(q) => q.key.as_deref(),
+            Question::DateTime(q) => q.key.as_deref(),
+        }
+    }
+    /// The name answers to the question are submitted under: its stable
+    /// `key` if it has one, otherwise its id.
+    pub fn property_name(&self) -> String {
+        match self.key() {
+            Some(key) => key.to_string(),
+            None => self.get_id().to_string(),
+        }
+    }
+    pub fn get_title(&self) -> &str {
+        match self {
+            Question::Integer(q) => &q.title,
+            Question::Number(q) => &q.title,
+            Question::FreeText(q) => &q.title,
+            Question::TrueOrFalse(q) => &q.title,
+            Question::FixedList(q) => &q.title,
+            Question::ArrayOf(q) => &q.title,
+            Question::Group(q) => &q.title,
+            Question::Date(q) => &q.title,
+            Question::Time(q) => &q.title,
+            Question::DateTime(q) => &q.title,
+        }
+    }
+    pub fn visible_if(&self) -> Option<&question::Condition> {
+        match self {
+            Question::Integer(q) => q.visible_if.as_ref(),
+            Question::Number(q) => q.visible_if.as_ref(),
+            Question::FreeText(q) => q.visible_if.as_ref(),
+            Question::TrueOrFalse(q) => q.visible_if.as_ref(),
+            Question::FixedList(q) => q.visible_if.as_ref(),
+            Question::ArrayOf(q) => q.visible_if.as_ref(),
+            Question::Group(q) => q.visible_if.as_ref(),
+            Question::Date(q) => q.visible_if.as_ref(),
+            Question::Time(q) => q.visible_if.as_ref(),
+            Question::DateTime(q) => q.visible_if.as_ref(),
         }
     }
     pub fn is_required(&self) -> bool {
         match self {
             Question::Integer(q) => q.required,
+            Question::Number(q) => q.required,
             Question::FreeText(q) => q.required,
             Question::TrueOrFalse(q) => q.required,
             Question::FixedList(q) => q.required,
             Question::ArrayOf(q) => q.required,
+            Question::Group(q) => q.required,
+            Question::Date(q) => q.required,
+            Question::Time(q) => q.required,
+            Question::DateTime(q) => q.required,
         }
     }
 }
 
 pub mod question {
+    use rust_decimal::Decimal;
+    use serde_json::Value;
+
+    /// Keys are used as JSON property names and in exports, so they are kept
+    /// to identifiers; a leading digit could also clash with a numeric id.
+    pub fn is_valid_key(key: &str) -> bool {
+        let mut chars = key.chars();
+        chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+
+    /// Shows a question only while another question has a given answer.
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct Condition {
+        pub question_id: i32,
+        pub equals: Value,
+    }
+
+    impl Condition {
+        /// Whether the condition holds for the answer to `question_id`, if any.
+        pub fn holds(&self, answer: Option<&Value>) -> bool {
+            answer == Some(&self.equals)
+        }
+    }
 
     #[derive(Debug, Deserialize, Serialize, Clone)]
     #[serde(deny_unknown_fields)]
     pub struct FreeText {
         pub id: i32,
+        pub key: Option<String>,
         pub title: String,
         pub description: String,
         pub default: Option<String>,
         pub required: bool,
+        pub visible_if: Option<Condition>,
         pub min_length: Option<i32>,
         pub max_length: Option<i32>,
+        /// ECMA-262 regular expression the answer must contain a match of.
+        pub pattern: Option<String>,
+        pub format: Option<TextFormat>,
+        pub ui: Option<ui::FreeText>,
+    }
+
+    /// The JSON Schema `format`s a free text answer can be held to.
+    #[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum TextFormat {
+        Email,
+        Uri,
+        Date,
+        DateTime,
+        Uuid,
+    }
+
+    impl TextFormat {
+        /// The name of the format in JSON Schema.
+        pub fn as_str(self) -> &'static str {
+            match self {
+                Self::Email => "email",
+                Self::Uri => "uri",
+                Self::Date => "date",
+                Self::DateTime => "date-time",
+                Self::Uuid => "uuid",
+            }
+        }
     }
 
     #[derive(Debug, Deserialize, Serialize, Clone)]
     #[serde(deny_unknown_fields)]
     pub struct TrueOrFalse {
         pub id: i32,
+        pub key: Option<String>,
         pub title: String,
         pub description: String,
         pub default: Option<bool>,
         pub required: bool,
+        pub visible_if: Option<Condition>,
         pub ui: Option<ui::TrueOrFalse>,
     }
 
@@ -66,40 +255,203 @@ pub mod question {
     #[serde(deny_unknown_fields)]
     pub struct Integer {
         pub id: i32,
+        pub key: Option<String>,
         pub title: String,
         pub description: String,
         pub required: bool,
+        pub visible_if: Option<Condition>,
         pub default: Option<i32>,
         pub step: Option<i32>,
         pub min: Option<i32>,
         pub max: Option<i32>,
+        pub ui: Option<ui::Integer>,
+    }
+
+    /// The most decimal places a `Decimal` can represent.
+    pub const MAX_PRECISION: u32 = 28;
+
+    /// The most significant digits a decimal keeps through the binary float
+    /// it is stored and served as.
+    pub const MAX_SIGNIFICANT_DIGITS: usize = 15;
+
+    /// The digits of `value` from its first non-zero one to its last.
+    pub fn significant_digits(value: Decimal) -> usize {
+        let digits: String = value
+            .to_string()
+            .chars()
+            .filter(char::is_ascii_digit)
+            .collect();
+        digits.trim_matches('0').len()
+    }
+
+    /// A decimal amount, compared exactly rather than as a float.
+    #[derive(Debug, Deserialize, Serialize, Clone)]
+    #[serde(deny_unknown_fields)]
+    pub struct Number {
+        pub id: i32,
+        pub key: Option<String>,
+        pub title: String,
+        pub description: String,
+        pub required: bool,
+        pub visible_if: Option<Condition>,
+        pub default: Option<Decimal>,
+        /// Most decimal places an answer may have.
+        pub precision: Option<u32>,
+        pub step: Option<Decimal>,
+        pub min: Option<Decimal>,
+        pub max: Option<Decimal>,
+        /// Label shown with the answer, such as `kg`, `AUD` or `%`.
+        pub unit: Option<String>,
+        pub ui: Option<ui::Number>,
+    }
+
+    impl Number {
+        /// What answers must be a multiple of: the step if given, otherwise
+        /// the smallest amount `precision` allows.
+        pub fn multiple_of(&self) -> Option<Decimal> {
+            self.step.or_else(|| {
+                self.precision
+                    .filter(|places| *places <= MAX_PRECISION)
+                    .map(|places| Decimal::new(1, places))
+            })
+        }
     }
 
     #[derive(Debug, Deserialize, Serialize, Clone)]
     #[serde(deny_unknown_fields)]
     pub struct FixedList {
         pub id: i32,
+        pub key: Option<String>,
         pub title: String,
         pub description: String,
         pub required: bool,
+        pub visible_if: Option<Condition>,
+        /// Only the first default is used in `Single` mode.
         pub default: Vec<String>,
         pub items: Vec<String>,
         pub item_names: Vec<String>,
+        #[serde(default)]
+        pub mode: SelectionMode,
+        /// Bounds on the number of items picked in `Multiple` mode.
+        pub min_selections: Option<i32>,
+        pub max_selections: Option<i32>,
+        pub ui: Option<ui::FixedList>,
+    }
+
+    #[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub enum SelectionMode {
+        #[default]
+        #[serde(rename = "single")]
+        Single,
+        #[serde(rename = "multiple")]
+        Multiple,
     }
 
     #[derive(Debug, Deserialize, Serialize, Clone)]
     #[serde(deny_unknown_fields)]
     pub struct ArrayOf {
         pub id: i32,
+        pub key: Option<String>,
         pub title: String,
         pub description: String,
         pub required: bool,
+        pub visible_if: Option<Condition>,
         pub question: super::Question,
+        /// Bounds on the number of times the question is answered.
+        pub min_items: Option<i32>,
+        pub max_items: Option<i32>,
+        pub ui: Option<ui::ArrayOf>,
+    }
+
+    /// Several questions answered together, such as the name, age and
+    /// relationship of one dependant when repeated by an `ArrayOf`.
+    #[derive(Debug, Deserialize, Serialize, Clone)]
+    #[serde(deny_unknown_fields)]
+    pub struct Group {
+        pub id: i32,
+        pub key: Option<String>,
+        pub title: String,
+        pub description: String,
+        pub required: bool,
+        pub visible_if: Option<Condition>,
+        pub questions: Vec<super::Question>,
+        pub ui: Option<ui::Field>,
+    }
+
+    pub use super::super::dates::DateBound;
+
+    #[derive(Debug, Deserialize, Serialize, Clone)]
+    #[serde(deny_unknown_fields)]
+    pub struct Date {
+        pub id: i32,
+        pub key: Option<String>,
+        pub title: String,
+        pub description: String,
+        pub required: bool,
+        pub visible_if: Option<Condition>,
+        pub default: Option<chrono::NaiveDate>,
+        pub min: Option<DateBound>,
+        pub max: Option<DateBound>,
+        /// IANA name of the timezone relative bounds count days in; UTC if
+        /// not given.
+        pub timezone: Option<String>,
+        pub ui: Option<ui::Field>,
+    }
+
+    /// A wall-clock time of day, without a date or offset.
+    #[derive(Debug, Deserialize, Serialize, Clone)]
+    #[serde(deny_unknown_fields)]
+    pub struct Time {
+        pub id: i32,
+        pub key: Option<String>,
+        pub title: String,
+        pub description: String,
+        pub required: bool,
+        pub visible_if: Option<Condition>,
+        pub default: Option<chrono::NaiveTime>,
+        pub min: Option<chrono::NaiveTime>,
+        pub max: Option<chrono::NaiveTime>,
+        pub ui: Option<ui::Field>,
+    }
+
+    /// An instant with an offset; bounds apply to its date in `timezone`.
+    #[derive(Debug, Deserialize, Serialize, Clone)]
+    #[serde(deny_unknown_fields)]
+    pub struct DateTime {
+        pub id: i32,
+        pub key: Option<String>,
+        pub title: String,
+        pub description: String,
+        pub required: bool,
+        pub visible_if: Option<Condition>,
+        pub default: Option<chrono::DateTime<chrono::FixedOffset>>,
+        pub min: Option<DateBound>,
+        pub max: Option<DateBound>,
+        pub timezone: Option<String>,
+        pub ui: Option<ui::Field>,
     }
 
     pub mod ui {
         use std::fmt;
 
+        /// Options every question's UI takes, rendered as the matching
+        /// `ui:` keywords. The per-type options embed them, so they sit
+        /// alongside the type's own ones in a document.
+        #[derive(Debug, Deserialize, Serialize, Clone, Default)]
+        #[serde(deny_unknown_fields)]
+        pub struct Field {
+            pub placeholder: Option<String>,
+            /// Text shown below the field.
+            pub help: Option<String>,
+            #[serde(default)]
+            pub autofocus: bool,
+            #[serde(default)]
+            pub readonly: bool,
+            #[serde(default)]
+            pub disabled: bool,
+        }
+
         #[derive(Debug, Deserialize, Serialize, Clone)]
         #[serde(deny_unknown_fields)]
         pub enum TrueOrFalseWidget {
@@ -113,6 +465,8 @@ pub mod question {
         #[serde(deny_unknown_fields)]
         pub struct TrueOrFalse {
             pub widget: Option<TrueOrFalseWidget>,
+            #[serde(flatten)]
+            pub field: Field,
         }
 
         #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -130,6 +484,104 @@ pub mod question {
         #[serde(deny_unknown_fields)]
         pub struct Integer {
             pub widget: Option<IntegerWidget>,
+            #[serde(flatten)]
+            pub field: Field,
+        }
+
+        #[derive(Debug, Deserialize, Serialize, Clone)]
+        #[serde(deny_unknown_fields)]
+        pub enum NumberWidget {
+            #[serde(rename = "updown")]
+            UpDown,
+            #[serde(rename = "range")]
+            Range,
+        }
+
+        #[derive(Debug, Deserialize, Serialize, Clone)]
+        #[serde(deny_unknown_fields)]
+        pub struct Number {
+            pub widget: Option<NumberWidget>,
+            #[serde(flatten)]
+            pub field: Field,
+        }
+
+        /// Widgets for free text; a single-line input if not given.
+        #[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+        #[serde(deny_unknown_fields)]
+        pub enum TextWidget {
+            #[serde(rename = "textarea")]
+            TextArea,
+            #[serde(rename = "password")]
+            Password,
+        }
+
+        /// The HTML input type of a single-line free text field, which
+        /// picks the keyboard shown on mobile devices.
+        #[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+        #[serde(deny_unknown_fields)]
+        #[serde(rename_all = "lowercase")]
+        pub enum InputType {
+            Text,
+            Email,
+            Tel,
+            Url,
+            Search,
+        }
+
+        #[derive(Debug, Deserialize, Serialize, Clone)]
+        #[serde(deny_unknown_fields)]
+        pub struct FreeText {
+            pub widget: Option<TextWidget>,
+            /// Visible lines of a `textarea`.
+            pub rows: Option<u32>,
+            pub input_type: Option<InputType>,
+            #[serde(flatten)]
+            pub field: Field,
+        }
+
+        /// Widgets for single selections; multiple selections always
+        /// render as checkboxes.
+        #[derive(Debug, Deserialize, Serialize, Clone)]
+        #[serde(deny_unknown_fields)]
+        pub enum FixedListWidget {
+            #[serde(rename = "radio")]
+            Radio,
+            #[serde(rename = "select")]
+            Select,
+        }
+
+        #[derive(Debug, Deserialize, Serialize, Clone)]
+        #[serde(deny_unknown_fields)]
+        pub struct FixedList {
+            pub widget: Option<FixedListWidget>,
+            /// Items shown but not selectable.
+            #[serde(default)]
+            pub enum_disabled: Vec<String>,
+            #[serde(flatten)]
+            pub field: Field,
+        }
+
+        #[derive(Debug, Deserialize, Serialize, Clone)]
+        #[serde(deny_unknown_fields)]
+        pub struct ArrayOf {
+            /// Whether entries can be moved up and down; they can if not
+            /// given.
+            pub orderable: Option<bool>,
+            #[serde(flatten)]
+            pub field: Field,
+        }
+
+        impl fmt::Display for FixedListWidget {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "{}",
+                    match self {
+                        Self::Radio => "radio",
+                        Self::Select => "select",
+                    }
+                )
+            }
         }
 
         impl fmt::Display for TrueOrFalseWidget {
@@ -144,23 +596,62 @@ pub mod question {
                 )
             }
         }
-    }
-}
 
-pub mod answer {
+        impl fmt::Display for IntegerWidget {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "{}",
+                    match self {
+                        Self::UpDown => "updown",
+                        Self::Range => "range",
+                        Self::Radio => "radio",
+                    }
+                )
+            }
+        }
 
-    #[derive(Debug, Deserialize, Serialize, Clone)]
-    #[serde(deny_unknown_fields)]
-    pub struct FreeText {
-        pub question_id: i32,
-        pub value: String,
-    }
+        impl fmt::Display for NumberWidget {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "{}",
+                    match self {
+                        Self::UpDown => "updown",
+                        Self::Range => "range",
+                    }
+                )
+            }
+        }
 
-    #[derive(Debug, Deserialize, Serialize, Clone)]
-    #[serde(deny_unknown_fields)]
-    pub struct TrueOrFalse {
-        pub question_id: i32,
-        pub value: bool,
+        impl fmt::Display for TextWidget {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "{}",
+                    match self {
+                        Self::TextArea => "textarea",
+                        Self::Password => "password",
+                    }
+                )
+            }
+        }
+
+        impl fmt::Display for InputType {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "{}",
+                    match self {
+                        Self::Text => "text",
+                        Self::Email => "email",
+                        Self::Tel => "tel",
+                        Self::Url => "url",
+                        Self::Search => "search",
+                    }
+                )
+            }
+        }
     }
 }
 
@@ -168,77 +659,213 @@ pub fn sample_string_questions() -> QuestionList {
     QuestionList {
         title: "Zork".into(),
         description: "Title zoek description".into(),
+        version: 1,
         questions: vec![
             Question::FreeText(question::FreeText {
                 id: 1,
+                key: None,
                 title: "What is your favourite food?".into(),
                 description: "Tell be about your food preferences".into(),
                 default: Some("Ice".into()),
                 required: true,
+                visible_if: None,
                 min_length: Some(0),
                 max_length: Some(64),
+                pattern: None,
+                format: None,
+                ui: None,
             }),
             Question::ArrayOf(Box::new(question::ArrayOf {
                 id: 8,
+                key: None,
                 title: "What is your favourite food?".into(),
                 description: "Tell be about your food preferences".into(),
                 required: true,
+                visible_if: None,
                 question: Question::FreeText(question::FreeText {
-                    id: 1,
+                    id: 81,
+                    key: None,
                     title: "What is your favourite food?".into(),
                     description: "Tell be about your food preferences".into(),
                     default: Some("Ice".into()),
                     required: true,
+                    visible_if: None,
                     min_length: Some(0),
                     max_length: Some(64),
+                    pattern: None,
+                    format: None,
+                    ui: None,
                 }),
+                min_items: None,
+                max_items: None,
+                ui: None,
             })),
             Question::Integer(question::Integer {
                 id: 88,
+                key: None,
                 title: "A number".into(),
                 description: "Some number".into(),
-                default: Some(1),
+                default: Some(10),
                 required: true,
+                visible_if: None,
                 step: Some(10),
                 min: Some(0),
                 max: Some(100),
+                ui: None,
             }),
             Question::TrueOrFalse(question::TrueOrFalse {
                 id: 222,
+                key: None,
                 title: "Some T/F 1".into(),
                 description: "Some T/f".into(),
                 default: Some(false),
                 required: true,
+                visible_if: None,
                 ui: None,
             }),
             Question::FixedList(question::FixedList {
                 id: 24,
+                key: None,
                 title: "Zork and or Bork?".into(),
                 description: "Some T/f".into(),
                 default: vec![],
                 required: true,
+                visible_if: None,
                 items: vec!["zork".to_string(), "bork".to_string()],
                 item_names: vec!["Zork".to_string(), "Bork".to_string()],
+                mode: question::SelectionMode::Single,
+                min_selections: None,
+                max_selections: None,
+                ui: None,
             }),
             Question::FreeText(question::FreeText {
                 id: 3,
+                key: None,
                 title: "Some title 2".into(),
                 description: "Some 2 desc".into(),
                 default: None,
                 required: true,
+                visible_if: Some(question::Condition {
+                    question_id: 222,
+                    equals: true.into(),
+                }),
                 min_length: Some(0),
                 max_length: Some(64),
+                pattern: None,
+                format: None,
+                ui: Some(question::ui::FreeText {
+                    widget: Some(question::ui::TextWidget::TextArea),
+                    rows: Some(4),
+                    input_type: None,
+                    field: question::ui::Field {
+                        placeholder: Some("Tell us more".into()),
+                        ..Default::default()
+                    },
+                }),
             }),
             Question::TrueOrFalse(question::TrueOrFalse {
                 id: 899,
+                key: None,
                 title: "I have read the terms and conditions".into(),
                 description: "Some T/f".into(),
                 default: Some(false),
                 required: true,
+                visible_if: None,
                 ui: Some(question::ui::TrueOrFalse {
                     widget: Some(question::ui::TrueOrFalseWidget::Radio),
+                    field: question::ui::Field {
+                        help: Some("You must agree to continue".into()),
+                        ..Default::default()
+                    },
                 }),
             }),
+            Question::FixedList(question::FixedList {
+                id: 25,
+                key: None,
+                title: "Any toppings?".into(),
+                description: "Pick up to two".into(),
+                default: vec!["sprinkles".to_string()],
+                required: false,
+                visible_if: None,
+                items: vec![
+                    "sprinkles".to_string(),
+                    "fudge".to_string(),
+                    "nuts".to_string(),
+                ],
+                item_names: vec![
+                    "Sprinkles".to_string(),
+                    "Fudge".to_string(),
+                    "Nuts".to_string(),
+                ],
+                mode: question::SelectionMode::Multiple,
+                min_selections: Some(1),
+                max_selections: Some(2),
+                ui: None,
+            }),
+            Question::ArrayOf(Box::new(question::ArrayOf {
+                id: 30,
+                key: Some("dependants".into()),
+                title: "Dependants".into(),
+                description: "List each of your dependants".into(),
+                required: false,
+                visible_if: None,
+                question: Question::Group(question::Group {
+                    id: 31,
+                    key: None,
+                    title: "Dependant".into(),
+                    description: "".into(),
+                    required: true,
+                    visible_if: None,
+                    questions: vec![
+                        Question::FreeText(question::FreeText {
+                            id: 32,
+                            key: Some("name".into()),
+                            title: "Name".into(),
+                            description: "".into(),
+                            default: None,
+                            required: true,
+                            visible_if: None,
+                            min_length: Some(1),
+                            max_length: None,
+                            pattern: None,
+                            format: None,
+                            ui: None,
+                        }),
+                        Question::Integer(question::Integer {
+                            id: 33,
+                            key: Some("age".into()),
+                            title: "Age".into(),
+                            description: "".into(),
+                            default: None,
+                            required: true,
+                            visible_if: None,
+                            step: None,
+                            min: Some(0),
+                            max: Some(150),
+                            ui: None,
+                        }),
+                        Question::FixedList(question::FixedList {
+                            id: 34,
+                            key: Some("relationship".into()),
+                            title: "Relationship".into(),
+                            description: "".into(),
+                            default: vec![],
+                            required: false,
+                            visible_if: None,
+                            items: vec!["child".to_string(), "partner".to_string()],
+                            item_names: vec!["Child".to_string(), "Partner".to_string()],
+                            mode: question::SelectionMode::Single,
+                            min_selections: None,
+                            max_selections: None,
+                            ui: None,
+                        }),
+                    ],
+                    ui: None,
+                }),
+                min_items: None,
+                max_items: Some(10),
+                ui: None,
+            })),
         ],
     }
 }
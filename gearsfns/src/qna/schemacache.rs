@@ -0,0 +1,129 @@
+//! Compiled answer schemas, kept for as long as a warm container lives so a
+//! submission does not rebuild and recompile its questionnaire's schema.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use jsonschema::JSONSchema;
+
+use super::questiondto::{Node, ObjectNode};
+use super::questionlist::QuestionList;
+use super::validation;
+use crate::Error;
+
+/// Compiled schemas by questionnaire id, each tagged with the version it was
+/// compiled from.
+#[derive(Default)]
+pub struct SchemaCache {
+    entries: Mutex<HashMap<String, (u32, Arc<JSONSchema>)>>,
+    /// Compile on every use and keep nothing.
+    bypass: bool,
+}
+
+impl SchemaCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A cache that keeps nothing, for stores whose questionnaires can change
+    /// without their version changing, such as hand-edited files.
+    pub fn bypassed() -> Self {
+        Self {
+            bypass: true,
+            ..Self::default()
+        }
+    }
+
+    /// The compiled schema of questionnaire `id`, compiling it on first use
+    /// and again whenever `question_list` has moved on to another version.
+    ///
+    /// Compiling happens without holding the lock, so requests for other
+    /// questionnaires are not held up by it; two requests missing the cache
+    /// at once may both compile. An older version never replaces a newer one.
+    pub fn get(&self, id: &str, question_list: &QuestionList) -> Result<Arc<JSONSchema>, Error> {
+        if self.bypass {
+            return compile(question_list).map(Arc::new);
+        }
+        if let Some((version, compiled)) = self.entries().get(id) {
+            if *version == question_list.version {
+                return Ok(compiled.clone());
+            }
+        }
+        let compiled = Arc::new(compile(question_list)?);
+        let mut entries = self.entries();
+        match entries.get(id) {
+            Some((version, _)) if *version > question_list.version => {}
+            _ => {
+                entries.insert(id.to_string(), (question_list.version, compiled.clone()));
+            }
+        }
+        Ok(compiled)
+    }
+
+    fn entries(&self) -> MutexGuard<'_, HashMap<String, (u32, Arc<JSONSchema>)>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Builds and compiles the answer schema of `question_list`.
+pub fn compile(question_list: &QuestionList) -> Result<JSONSchema, Error> {
+    let node = Node::Object(ObjectNode::try_from(question_list.clone())?);
+    let schema = serde_json::to_value(&node)?;
+    validation::compile(&schema).map_err(Error::Schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qna::questionlist;
+    use serde_json::json;
+
+    #[test]
+    fn recompiles_when_the_version_changes() {
+        let cache = SchemaCache::new();
+        let mut sample = questionlist::sample_string_questions();
+
+        let first = cache.get("sample", &sample).unwrap();
+        assert!(Arc::ptr_eq(&first, &cache.get("sample", &sample).unwrap()));
+
+        sample.questions.truncate(1);
+        sample.version += 1;
+        let second = cache.get("sample", &sample).unwrap();
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert_eq!(cache.len(), 1);
+
+        let answers = json!({ "1": "Ice" });
+        assert!(first.validate(&answers).is_err());
+        assert!(second.validate(&answers).is_ok());
+
+        // A request still holding the previous version does not evict the
+        // current one.
+        sample.version -= 1;
+        cache.get("sample", &sample).unwrap();
+        sample.version += 1;
+        assert!(Arc::ptr_eq(&second, &cache.get("sample", &sample).unwrap()));
+    }
+
+    #[test]
+    fn bypassed_cache_compiles_every_time() {
+        let cache = SchemaCache::bypassed();
+        let mut sample = questionlist::sample_string_questions();
+        let first = cache.get("sample", &sample).unwrap();
+
+        // Edited in place, as a file would be, without a new version.
+        sample.questions.truncate(1);
+        let second = cache.get("sample", &sample).unwrap();
+        assert!(cache.is_empty());
+        assert!(first.validate(&json!({ "1": "Ice" })).is_err());
+        assert!(second.validate(&json!({ "1": "Ice" })).is_ok());
+    }
+}
@@ -0,0 +1,797 @@
+//! Conversion from a Draft-7 JSON Schema object, such as the ones
+//! `questiondto::ObjectNode` emits, back into a `QuestionList`.
+//!
+//! Keywords the questionnaire model cannot represent are dropped and
+//! reported as warnings rather than failing the whole import.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::DateTime;
+use rust_decimal::Decimal;
+use serde_json::{Map, Value};
+
+use super::dates::{self, TIME_PATTERN};
+use super::questionlist::{question, Question, QuestionList};
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ImportWarning {
+    /// JSON pointer to the offending location in the imported schema.
+    pub pointer: String,
+    pub keyword: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Import {
+    pub question_list: QuestionList,
+    pub warnings: Vec<ImportWarning>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportError {
+    /// The root schema is not a JSON object.
+    NotAnObject,
+    /// The root schema does not describe an object with properties.
+    NotAnObjectSchema,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotAnObject => write!(f, "schema must be a JSON object"),
+            Self::NotAnObjectSchema => {
+                write!(f, "schema must have type \"object\" and a properties map")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+const ROOT_KEYWORDS: &[&str] = &[
+    "$schema",
+    "$id",
+    "type",
+    "title",
+    "description",
+    "properties",
+    "required",
+    "additionalProperties",
+    "allOf",
+];
+const COMMON_KEYWORDS: &[&str] = &["type", "title", "description", "default"];
+const INTEGER_KEYWORDS: &[&str] = &["minimum", "maximum", "multipleOf"];
+const STRING_KEYWORDS: &[&str] = &["minLength", "maxLength", "pattern", "format"];
+const DATE_KEYWORDS: &[&str] = &["format"];
+const TIME_KEYWORDS: &[&str] = &["pattern"];
+const ENUM_KEYWORDS: &[&str] = &["enum", "enumNames"];
+const ARRAY_KEYWORDS: &[&str] = &["items", "minItems", "maxItems"];
+const GROUP_KEYWORDS: &[&str] = &["properties", "required", "additionalProperties"];
+const MULTI_SELECT_KEYWORDS: &[&str] = &["items", "uniqueItems", "minItems", "maxItems"];
+
+fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Imports a JSON Schema object describing a questionnaire.
+pub fn import(schema: &Value) -> Result<Import, ImportError> {
+    let root = schema.as_object().ok_or(ImportError::NotAnObject)?;
+    let properties = match (root.get("type"), root.get("properties")) {
+        (Some(Value::String(t)), Some(Value::Object(properties))) if t == "object" => properties,
+        _ => return Err(ImportError::NotAnObjectSchema),
+    };
+
+    let mut importer = Importer {
+        warnings: vec![],
+        next_id: largest_id(root) + 1,
+    };
+    importer.unsupported_keywords("", root, &[ROOT_KEYWORDS]);
+    if let Some(additional) = root.get("additionalProperties") {
+        if additional != &Value::Bool(false) {
+            importer.warn(
+                "/additionalProperties",
+                "additionalProperties",
+                "questionnaires never accept additional properties",
+            );
+        }
+    }
+
+    let required: Vec<&str> = root
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let mut ids = HashMap::new();
+    for key in properties.keys() {
+        let id = importer.id(&format!("/properties/{}", escape(key)), key);
+        ids.insert(key.as_str(), id);
+    }
+
+    let mut conditions = HashMap::new();
+    if let Some(all_of) = root.get("allOf") {
+        let entries = all_of.as_array().map(Vec::as_slice).unwrap_or_default();
+        for (index, entry) in entries.iter().enumerate() {
+            match visibility(entry).and_then(|(key, on, equals, required)| {
+                let condition = question::Condition {
+                    question_id: *ids.get(on.as_str())?,
+                    equals,
+                };
+                Some((ids.get(key.as_str())?, (condition, required)))
+            }) {
+                Some((id, condition)) => {
+                    conditions.insert(*id, condition);
+                }
+                None => importer.warn(
+                    &format!("/allOf/{}", index),
+                    "allOf",
+                    "only visibility conditions on questions are supported, entry dropped",
+                ),
+            }
+        }
+    }
+
+    let questions = properties
+        .iter()
+        .filter_map(|(key, property)| {
+            let pointer = format!("/properties/{}", escape(key));
+            let id = ids[key.as_str()];
+            let (visible_if, required_if_visible) = match conditions.remove(&id) {
+                Some((condition, required)) => (Some(condition), required),
+                None => (None, false),
+            };
+            importer.question(
+                &pointer,
+                id,
+                Some(key),
+                required.contains(&key.as_str()) || required_if_visible,
+                visible_if,
+                property,
+            )
+        })
+        .collect();
+
+    Ok(Import {
+        question_list: QuestionList {
+            title: string(root, "title").unwrap_or_default(),
+            description: string(root, "description").unwrap_or_default(),
+            version: 0,
+            questions,
+        },
+        warnings: importer.warnings,
+    })
+}
+
+/// The largest numeric property name anywhere in `node`, so that fresh ids
+/// never collide with nested questions.
+fn largest_id(node: &Map<String, Value>) -> i32 {
+    let items = node
+        .get("items")
+        .and_then(Value::as_object)
+        .map_or(0, largest_id);
+    node.get("properties")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .map(|(key, property)| {
+            let nested = property.as_object().map_or(0, largest_id);
+            key.parse::<i32>().unwrap_or(0).max(nested)
+        })
+        .fold(items, i32::max)
+}
+
+fn string(node: &Map<String, Value>, keyword: &str) -> Option<String> {
+    node.get(keyword).and_then(Value::as_str).map(String::from)
+}
+
+/// Recognises the `if`/`then`/`else` entry `questiondto::Conditional` emits,
+/// returning the question it hides, the question and answer it depends on
+/// and whether the question is required while visible.
+fn visibility(entry: &Value) -> Option<(String, String, Value, bool)> {
+    fn single(clause: Option<&Value>) -> Option<(&String, &Value)> {
+        let properties = clause?.get("properties")?.as_object()?;
+        match properties.len() {
+            1 => properties.iter().next(),
+            _ => None,
+        }
+    }
+
+    let entry = entry.as_object()?;
+    if entry
+        .keys()
+        .any(|k| !["if", "then", "else"].contains(&k.as_str()))
+    {
+        return None;
+    }
+    let (on, constant) = single(entry.get("if"))?;
+    let equals = constant
+        .as_object()
+        .filter(|c| c.len() == 1)?
+        .get("const")?;
+    let (key, hidden) = single(entry.get("else"))?;
+    if hidden != &Value::Bool(false) {
+        return None;
+    }
+    let required = match entry.get("then") {
+        None => false,
+        Some(then) => {
+            if then.get("required")? != &Value::from(vec![key.as_str()]) {
+                return None;
+            }
+            true
+        }
+    };
+    Some((key.clone(), on.clone(), equals.clone(), required))
+}
+
+fn strings(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::Array(values)) => values
+            .iter()
+            .filter_map(Value::as_str)
+            .map(String::from)
+            .collect(),
+        Some(Value::String(value)) => vec![value.clone()],
+        _ => vec![],
+    }
+}
+
+struct Importer {
+    warnings: Vec<ImportWarning>,
+    next_id: i32,
+}
+
+impl Importer {
+    fn warn(&mut self, pointer: &str, keyword: &str, message: &str) {
+        self.warnings.push(ImportWarning {
+            pointer: pointer.to_string(),
+            keyword: keyword.to_string(),
+            message: message.to_string(),
+        });
+    }
+
+    fn fresh_id(&mut self) -> i32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    /// The question id a property name stands for, or a fresh one if it is
+    /// a key instead.
+    fn id(&mut self, pointer: &str, name: &str) -> i32 {
+        if let Ok(id) = name.parse::<i32>() {
+            return id;
+        }
+        let id = self.fresh_id();
+        if !question::is_valid_key(name) {
+            self.warn(
+                pointer,
+                "properties",
+                &format!(
+                    "property \"{}\" is neither a question id nor a valid key, imported as question {}",
+                    name, id
+                ),
+            );
+        }
+        id
+    }
+
+    fn unsupported_keywords(
+        &mut self,
+        pointer: &str,
+        node: &Map<String, Value>,
+        known: &[&[&str]],
+    ) {
+        for keyword in node.keys() {
+            if !known
+                .iter()
+                .any(|keywords| keywords.contains(&keyword.as_str()))
+            {
+                self.warn(
+                    &format!("{}/{}", pointer, escape(keyword)),
+                    keyword,
+                    "keyword is not supported and was dropped",
+                );
+            }
+        }
+    }
+
+    fn integer(&mut self, pointer: &str, node: &Map<String, Value>, keyword: &str) -> Option<i32> {
+        let value = node.get(keyword)?;
+        match value
+            .as_i64()
+            .filter(|v| i32::MIN as i64 <= *v && *v <= i32::MAX as i64)
+        {
+            Some(v) => Some(v as i32),
+            None => {
+                if !value.is_null() {
+                    self.warn(
+                        &format!("{}/{}", pointer, keyword),
+                        keyword,
+                        "value is not a 32-bit integer and was dropped",
+                    );
+                }
+                None
+            }
+        }
+    }
+
+    fn decimal(
+        &mut self,
+        pointer: &str,
+        node: &Map<String, Value>,
+        keyword: &str,
+    ) -> Option<Decimal> {
+        let value = node.get(keyword)?;
+        match value
+            .as_f64()
+            .and(serde_json::from_value(value.clone()).ok())
+        {
+            Some(v) => Some(v),
+            None => {
+                if !value.is_null() {
+                    self.warn(
+                        &format!("{}/{}", pointer, keyword),
+                        keyword,
+                        "value is not a decimal number and was dropped",
+                    );
+                }
+                None
+            }
+        }
+    }
+
+    fn format(&mut self, pointer: &str, node: &Map<String, Value>) -> Option<question::TextFormat> {
+        let value = node.get("format")?;
+        match serde_json::from_value(value.clone()) {
+            Ok(format) => Some(format),
+            Err(_) => {
+                self.warn(
+                    &format!("{}/format", pointer),
+                    "format",
+                    &format!("format {} is not supported and was dropped", value),
+                );
+                None
+            }
+        }
+    }
+
+    /// Parses `default` with `parse`, dropping it with a warning if it does
+    /// not hold a valid value.
+    fn parsed_default<T>(
+        &mut self,
+        pointer: &str,
+        node: &Map<String, Value>,
+        parse: impl Fn(&str) -> Option<T>,
+    ) -> Option<T> {
+        let value = node.get("default")?;
+        let parsed = value.as_str().and_then(parse);
+        if parsed.is_none() {
+            self.warn(
+                &format!("{}/default", pointer),
+                "default",
+                &format!("default {} is not valid and was dropped", value),
+            );
+        }
+        parsed
+    }
+
+    /// The `enum` values of `node` and their `enumNames`, falling back to the
+    /// values themselves when the names do not line up.
+    fn enumeration(
+        &mut self,
+        pointer: &str,
+        node: &Map<String, Value>,
+    ) -> (Vec<String>, Vec<String>) {
+        let items = strings(node.get("enum"));
+        let mut item_names = strings(node.get("enumNames"));
+        if item_names.len() != items.len() {
+            if !item_names.is_empty() {
+                self.warn(
+                    &format!("{}/enumNames", pointer),
+                    "enumNames",
+                    "enumNames does not match enum and was replaced by the enum values",
+                );
+            }
+            item_names = items.clone();
+        }
+        (items, item_names)
+    }
+
+    fn question(
+        &mut self,
+        pointer: &str,
+        id: i32,
+        name: Option<&str>,
+        required: bool,
+        visible_if: Option<question::Condition>,
+        property: &Value,
+    ) -> Option<Question> {
+        let node = match property.as_object() {
+            Some(node) => node,
+            None => {
+                self.warn(
+                    pointer,
+                    "properties",
+                    "property schema is not an object, skipped",
+                );
+                return None;
+            }
+        };
+        let title = string(node, "title").unwrap_or_else(|| name.unwrap_or_default().into());
+        let key = name
+            .filter(|name| name.parse::<i32>().is_err() && question::is_valid_key(name))
+            .map(String::from);
+        let description = string(node, "description").unwrap_or_default();
+
+        match node.get("type").and_then(Value::as_str) {
+            Some("integer") => {
+                self.unsupported_keywords(pointer, node, &[COMMON_KEYWORDS, INTEGER_KEYWORDS]);
+                Some(Question::Integer(question::Integer {
+                    id,
+                    key: key.clone(),
+                    title,
+                    description,
+                    required,
+                    visible_if,
+                    default: self.integer(pointer, node, "default"),
+                    step: self.integer(pointer, node, "multipleOf"),
+                    min: self.integer(pointer, node, "minimum"),
+                    max: self.integer(pointer, node, "maximum"),
+                    ui: None,
+                }))
+            }
+            Some("string") if node.contains_key("enum") => {
+                self.unsupported_keywords(pointer, node, &[COMMON_KEYWORDS, ENUM_KEYWORDS]);
+                let (items, item_names) = self.enumeration(pointer, node);
+                Some(Question::FixedList(question::FixedList {
+                    id,
+                    key: key.clone(),
+                    title,
+                    description,
+                    required,
+                    visible_if,
+                    default: strings(node.get("default")),
+                    items,
+                    item_names,
+                    mode: question::SelectionMode::Single,
+                    min_selections: None,
+                    max_selections: None,
+                    ui: None,
+                }))
+            }
+            Some("array") if node.get("items").and_then(|i| i.get("enum")).is_some() => {
+                self.unsupported_keywords(pointer, node, &[COMMON_KEYWORDS, MULTI_SELECT_KEYWORDS]);
+                let items_pointer = format!("{}/items", pointer);
+                let item_node = node
+                    .get("items")
+                    .and_then(Value::as_object)
+                    .cloned()
+                    .unwrap_or_default();
+                self.unsupported_keywords(&items_pointer, &item_node, &[&["type"], ENUM_KEYWORDS]);
+                if node.get("uniqueItems") != Some(&Value::Bool(true)) {
+                    self.warn(
+                        &format!("{}/uniqueItems", pointer),
+                        "uniqueItems",
+                        "selections are always unique",
+                    );
+                }
+                let (items, item_names) = self.enumeration(&items_pointer, &item_node);
+                Some(Question::FixedList(question::FixedList {
+                    id,
+                    key: key.clone(),
+                    title,
+                    description,
+                    required,
+                    visible_if,
+                    default: strings(node.get("default")),
+                    items,
+                    item_names,
+                    mode: question::SelectionMode::Multiple,
+                    min_selections: self.integer(pointer, node, "minItems"),
+                    max_selections: self.integer(pointer, node, "maxItems"),
+                    ui: None,
+                }))
+            }
+            Some("number") => {
+                self.unsupported_keywords(pointer, node, &[COMMON_KEYWORDS, INTEGER_KEYWORDS]);
+                Some(Question::Number(question::Number {
+                    id,
+                    key: key.clone(),
+                    title,
+                    description,
+                    required,
+                    visible_if,
+                    default: self.decimal(pointer, node, "default"),
+                    precision: None,
+                    step: self.decimal(pointer, node, "multipleOf"),
+                    min: self.decimal(pointer, node, "minimum"),
+                    max: self.decimal(pointer, node, "maximum"),
+                    unit: None,
+                    ui: None,
+                }))
+            }
+            Some("string") if string(node, "format").as_deref() == Some("date") => {
+                self.unsupported_keywords(pointer, node, &[COMMON_KEYWORDS, DATE_KEYWORDS]);
+                Some(Question::Date(question::Date {
+                    id,
+                    key: key.clone(),
+                    title,
+                    description,
+                    required,
+                    visible_if,
+                    default: self.parsed_default(pointer, node, dates::parse_date),
+                    min: None,
+                    max: None,
+                    timezone: None,
+                    ui: None,
+                }))
+            }
+            Some("string") if string(node, "format").as_deref() == Some("date-time") => {
+                self.unsupported_keywords(pointer, node, &[COMMON_KEYWORDS, DATE_KEYWORDS]);
+                Some(Question::DateTime(question::DateTime {
+                    id,
+                    key: key.clone(),
+                    title,
+                    description,
+                    required,
+                    visible_if,
+                    default: self
+                        .parsed_default(pointer, node, |s| DateTime::parse_from_rfc3339(s).ok()),
+                    min: None,
+                    max: None,
+                    timezone: None,
+                    ui: None,
+                }))
+            }
+            Some("string")
+                if string(node, "pattern").as_deref() == Some(TIME_PATTERN)
+                    && !node.contains_key("format") =>
+            {
+                self.unsupported_keywords(pointer, node, &[COMMON_KEYWORDS, TIME_KEYWORDS]);
+                Some(Question::Time(question::Time {
+                    id,
+                    key: key.clone(),
+                    title,
+                    description,
+                    required,
+                    visible_if,
+                    default: self.parsed_default(pointer, node, dates::parse_time),
+                    min: None,
+                    max: None,
+                    ui: None,
+                }))
+            }
+            Some("string") => {
+                self.unsupported_keywords(pointer, node, &[COMMON_KEYWORDS, STRING_KEYWORDS]);
+                Some(Question::FreeText(question::FreeText {
+                    id,
+                    key: key.clone(),
+                    title,
+                    description,
+                    default: string(node, "default"),
+                    required,
+                    visible_if,
+                    min_length: self.integer(pointer, node, "minLength"),
+                    max_length: self.integer(pointer, node, "maxLength"),
+                    pattern: string(node, "pattern"),
+                    format: self.format(pointer, node),
+                    ui: None,
+                }))
+            }
+            Some("boolean") => {
+                self.unsupported_keywords(pointer, node, &[COMMON_KEYWORDS]);
+                Some(Question::TrueOrFalse(question::TrueOrFalse {
+                    id,
+                    key: key.clone(),
+                    title,
+                    description,
+                    default: node.get("default").and_then(Value::as_bool),
+                    required,
+                    visible_if,
+                    ui: None,
+                }))
+            }
+            Some("array") => {
+                self.unsupported_keywords(pointer, node, &[COMMON_KEYWORDS, ARRAY_KEYWORDS]);
+                let items_pointer = format!("{}/items", pointer);
+                let item_id = self.fresh_id();
+                let items = node.get("items").unwrap_or(&Value::Null);
+                let item = self.question(&items_pointer, item_id, None, false, None, items)?;
+                Some(Question::ArrayOf(Box::new(question::ArrayOf {
+                    id,
+                    key: key.clone(),
+                    title,
+                    description,
+                    required,
+                    visible_if,
+                    question: item,
+                    min_items: self.integer(pointer, node, "minItems"),
+                    max_items: self.integer(pointer, node, "maxItems"),
+                    ui: None,
+                })))
+            }
+            Some("object") => {
+                self.unsupported_keywords(pointer, node, &[COMMON_KEYWORDS, GROUP_KEYWORDS]);
+                if node
+                    .get("additionalProperties")
+                    .is_some_and(|additional| additional != &Value::Bool(false))
+                {
+                    self.warn(
+                        &format!("{}/additionalProperties", pointer),
+                        "additionalProperties",
+                        "groups never accept additional properties",
+                    );
+                }
+                let members_required = strings(node.get("required"));
+                let questions = node
+                    .get("properties")
+                    .and_then(Value::as_object)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|(key, property)| {
+                        let pointer = format!("{}/properties/{}", pointer, escape(key));
+                        let id = self.id(&pointer, key);
+                        let required = members_required.contains(key);
+                        self.question(&pointer, id, Some(key), required, None, property)
+                    })
+                    .collect();
+                Some(Question::Group(question::Group {
+                    id,
+                    key: key.clone(),
+                    title,
+                    description,
+                    required,
+                    visible_if,
+                    questions,
+                    ui: None,
+                }))
+            }
+            Some(other) => {
+                self.warn(
+                    &format!("{}/type", pointer),
+                    "type",
+                    &format!("type \"{}\" is not supported, property skipped", other),
+                );
+                None
+            }
+            None => {
+                self.warn(pointer, "type", "property has no type, skipped");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qna::{questiondto, questionlist};
+    use serde_json::json;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn round_trips_emitted_schema() {
+        let sample = questionlist::sample_string_questions();
+        let node = questiondto::ObjectNode::try_from(sample.clone()).unwrap();
+        let schema = serde_json::to_value(questiondto::Node::Object(node)).unwrap();
+
+        let import = import(&schema).unwrap();
+        assert_eq!(import.warnings, vec![]);
+        assert_eq!(import.question_list.title, sample.title);
+
+        let summary = |q: &Question| (q.property_name(), q.is_required(), q.visible_if().cloned());
+        let imported: Vec<_> = import.question_list.questions.iter().map(summary).collect();
+        let expected: Vec<_> = sample.questions.iter().map(summary).collect();
+        assert_eq!(imported, expected);
+    }
+
+    #[test]
+    fn reports_unsupported_keywords() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "age": { "type": "integer", "exclusiveMinimum": 0 },
+                "2": { "type": "null" },
+            },
+            "required": ["age"],
+        });
+
+        let import = import(&schema).unwrap();
+        assert_eq!(import.question_list.questions.len(), 1);
+        assert_eq!(import.question_list.questions[0].get_id(), 3);
+        assert_eq!(import.question_list.questions[0].key(), Some("age"));
+        assert!(import.question_list.questions[0].is_required());
+
+        let mut pointers: Vec<&str> = import.warnings.iter().map(|w| w.pointer.as_str()).collect();
+        pointers.sort();
+        assert_eq!(
+            pointers,
+            vec!["/properties/2/type", "/properties/age/exclusiveMinimum"]
+        );
+    }
+
+    #[test]
+    fn imports_dates_and_times() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "1": { "type": "string", "format": "date", "default": "2024-02-29" },
+                "2": { "type": "string", "format": "date-time", "default": "tomorrow" },
+                "3": { "type": "string", "pattern": TIME_PATTERN, "default": "09:30" },
+            },
+        });
+
+        let import = import(&schema).unwrap();
+        let questions = serde_json::to_value(&import.question_list.questions).unwrap();
+        assert_eq!(questions[0]["Date"]["default"], json!("2024-02-29"));
+        assert_eq!(questions[1]["DateTime"]["default"], Value::Null);
+        assert_eq!(questions[2]["Time"]["default"], json!("09:30:00"));
+        let pointers: Vec<&str> = import.warnings.iter().map(|w| w.pointer.as_str()).collect();
+        assert_eq!(pointers, vec!["/properties/2/default"]);
+    }
+
+    #[test]
+    fn imports_repeated_groups() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "1": {
+                    "type": "array",
+                    "maxItems": 4,
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string" },
+                            "7": { "type": "integer" },
+                        },
+                        "required": ["name"],
+                    },
+                },
+            },
+        });
+
+        let import = import(&schema).unwrap();
+        let questions = serde_json::to_value(&import.question_list.questions).unwrap();
+        let array = &questions[0]["ArrayOf"];
+        assert_eq!(array["max_items"], json!(4));
+        let members = &array["question"]["Group"]["questions"];
+        assert_eq!(members[0]["FreeText"]["id"], json!(9));
+        assert_eq!(members[0]["FreeText"]["key"], json!("name"));
+        assert_eq!(members[0]["FreeText"]["required"], json!(true));
+        assert_eq!(members[1]["Integer"]["id"], json!(7));
+        assert_eq!(import.warnings, vec![]);
+    }
+
+    #[test]
+    fn imports_numbers_exactly() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "1": { "type": "number", "multipleOf": 0.01, "minimum": 0.1, "default": "x" },
+            },
+        });
+
+        let import = import(&schema).unwrap();
+        match &import.question_list.questions[0] {
+            Question::Number(q) => {
+                assert_eq!(q.step, Some(Decimal::new(1, 2)));
+                assert_eq!(q.min, Some(Decimal::new(1, 1)));
+                assert_eq!(q.default, None);
+            }
+            other => panic!("imported {:?}", other),
+        }
+        let pointers: Vec<&str> = import.warnings.iter().map(|w| w.pointer.as_str()).collect();
+        assert_eq!(pointers, vec!["/properties/1/default"]);
+    }
+
+    #[test]
+    fn rejects_non_object_schema() {
+        assert_eq!(
+            import(&json!({ "type": "string" })).unwrap_err(),
+            ImportError::NotAnObjectSchema
+        );
+    }
+}
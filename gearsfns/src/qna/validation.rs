@@ -0,0 +1,304 @@
+//! Validation errors that point back at the questions they concern.
+
+use jsonschema::error::{TypeKind, ValidationErrorKind};
+use jsonschema::{Draft, JSONSchema};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::answer;
+use super::questionlist::{Question, QuestionList};
+
+/// What was wrong with an answer, with the limits involved.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    Required,
+    Type(String),
+    MinLength(u64),
+    MaxLength(u64),
+    Pattern(String),
+    Format(String),
+    Minimum(String),
+    Maximum(String),
+    /// Dates and times earlier than the bound.
+    NotBefore(String),
+    NotAfter(String),
+    MultipleOf(String),
+    Enum(Vec<String>),
+    MinItems(u64),
+    MaxItems(u64),
+    UniqueItems,
+    AdditionalProperty,
+    /// Answered although its visibility condition does not hold.
+    Hidden,
+    Other(String),
+}
+
+impl Violation {
+    /// Machine-readable code, named after the JSON Schema keyword.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Required => "required",
+            Self::Type(_) => "type",
+            Self::MinLength(_) => "minLength",
+            Self::MaxLength(_) => "maxLength",
+            Self::Pattern(_) => "pattern",
+            Self::Format(_) => "format",
+            Self::Minimum(_) => "minimum",
+            Self::Maximum(_) => "maximum",
+            Self::NotBefore(_) => "formatMinimum",
+            Self::NotAfter(_) => "formatMaximum",
+            Self::MultipleOf(_) => "multipleOf",
+            Self::Enum(_) => "enum",
+            Self::MinItems(_) => "minItems",
+            Self::MaxItems(_) => "maxItems",
+            Self::UniqueItems => "uniqueItems",
+            Self::AdditionalProperty => "additionalProperties",
+            Self::Hidden => "hidden",
+            Self::Other(_) => "invalid",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            Self::Required => "an answer is required".into(),
+            Self::Type(expected) => format!("must be of type {}", expected),
+            Self::MinLength(limit) => format!("must be at least {} characters long", limit),
+            Self::MaxLength(limit) => format!("must be at most {} characters long", limit),
+            Self::Pattern(pattern) => format!("must match the pattern {}", pattern),
+            Self::Format(format) => format!("must be a valid {}", format),
+            Self::Minimum(limit) => format!("must be at least {}", limit),
+            Self::Maximum(limit) => format!("must be at most {}", limit),
+            Self::NotBefore(limit) => format!("must not be before {}", limit),
+            Self::NotAfter(limit) => format!("must not be after {}", limit),
+            Self::MultipleOf(step) => format!("must be a multiple of {}", step),
+            Self::Enum(options) => format!("must be one of {}", options.join(", ")),
+            Self::MinItems(limit) => format!("must have at least {} items", limit),
+            Self::MaxItems(limit) => format!("must have at most {} items", limit),
+            Self::UniqueItems => "must not contain the same item twice".into(),
+            Self::AdditionalProperty => "is not a question in this questionnaire".into(),
+            Self::Hidden => "must be left unanswered while the question is hidden".into(),
+            Self::Other(message) => message.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationError {
+    /// JSON pointer to the offending value in the submitted answers.
+    pub pointer: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub question_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl ValidationError {
+    pub fn new(question_list: &QuestionList, path: &[String], violation: Violation) -> Self {
+        let question = find_question(&question_list.questions, path);
+        ValidationError {
+            pointer: pointer(path),
+            question_id: question.map(Question::get_id),
+            title: question.map(|q| q.get_title().to_string()),
+            code: violation.code(),
+            message: violation.message(),
+        }
+    }
+}
+
+/// The question an answer path points into: the first segment names a
+/// question, array indices step into `ArrayOf` items and property names into
+/// `Group` members.
+pub fn find_question<'a>(questions: &'a [Question], path: &[String]) -> Option<&'a Question> {
+    let (first, rest) = path.split_first()?;
+    let mut question = questions.iter().find(|q| q.property_name() == *first)?;
+    for segment in rest {
+        match question {
+            Question::ArrayOf(array) if segment.parse::<usize>().is_ok() => {
+                question = &array.question
+            }
+            Question::Group(group) => {
+                match group
+                    .questions
+                    .iter()
+                    .find(|q| q.property_name() == *segment)
+                {
+                    Some(member) => question = member,
+                    None => break,
+                }
+            }
+            _ => break,
+        }
+    }
+    Some(question)
+}
+
+/// Renders path segments as an RFC 6901 JSON pointer.
+pub fn pointer(path: &[String]) -> String {
+    path.iter()
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
+
+/// Maps a `jsonschema` error onto the questions it concerns; an unexpected
+/// property error yields one entry per property.
+pub fn from_schema_error(
+    question_list: &QuestionList,
+    error: &jsonschema::ValidationError,
+) -> Vec<ValidationError> {
+    let mut path = error.instance_path.clone().into_vec();
+    let violation = match &error.kind {
+        ValidationErrorKind::Required { property } => {
+            path.push(property.as_str().unwrap_or_default().to_string());
+            Violation::Required
+        }
+        ValidationErrorKind::AdditionalProperties { unexpected } => {
+            return unexpected
+                .iter()
+                .map(|property| {
+                    let mut path = path.clone();
+                    path.push(property.clone());
+                    ValidationError::new(question_list, &path, Violation::AdditionalProperty)
+                })
+                .collect()
+        }
+        ValidationErrorKind::Type {
+            kind: TypeKind::Single(expected),
+        } => Violation::Type(expected.to_string()),
+        ValidationErrorKind::MinLength { limit } => Violation::MinLength(*limit),
+        ValidationErrorKind::MaxLength { limit } => Violation::MaxLength(*limit),
+        ValidationErrorKind::Pattern { pattern } => Violation::Pattern(pattern.clone()),
+        ValidationErrorKind::Format { format } => Violation::Format(format.to_string()),
+        ValidationErrorKind::Minimum { limit } => Violation::Minimum(limit.to_string()),
+        ValidationErrorKind::Maximum { limit } => Violation::Maximum(limit.to_string()),
+        ValidationErrorKind::MultipleOf { multiple_of } => {
+            Violation::MultipleOf(multiple_of.to_string())
+        }
+        ValidationErrorKind::Enum { options } => Violation::Enum(
+            options
+                .as_array()
+                .map(|options| {
+                    options
+                        .iter()
+                        .map(|o| {
+                            o.as_str()
+                                .map(String::from)
+                                .unwrap_or_else(|| o.to_string())
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        ),
+        ValidationErrorKind::MinItems { limit } => Violation::MinItems(*limit),
+        ValidationErrorKind::MaxItems { limit } => Violation::MaxItems(*limit),
+        ValidationErrorKind::UniqueItems => Violation::UniqueItems,
+        // Only the `else` branch of a visibility condition emits `false`.
+        ValidationErrorKind::FalseSchema => Violation::Hidden,
+        _ => Violation::Other(error.to_string()),
+    };
+    vec![ValidationError::new(question_list, &path, violation)]
+}
+
+/// Compiles a questionnaire schema as Draft 7, which leaves the `uuid`
+/// format unchecked unless it is registered. Fails with the reason the
+/// schema does not compile.
+pub fn compile(schema: &Value) -> Result<JSONSchema, String> {
+    JSONSchema::options()
+        .with_draft(Draft::Draft7)
+        .with_format("uuid", |value| Uuid::parse_str(value).is_ok())
+        .compile(schema)
+        .map_err(|err| err.to_string())
+}
+
+/// Validates answers against the compiled schema of `question_list`.
+///
+/// The schema alone is not authoritative: it checks `multipleOf` in binary
+/// floats, so clients running it may reject decimal answers, or accept ones
+/// off the step, where `validator::validate` decides exactly. Submissions are
+/// always checked by both.
+pub fn validate_with_schema(
+    question_list: &QuestionList,
+    schema: &JSONSchema,
+    answers: &Value,
+) -> Result<(), Vec<ValidationError>> {
+    let errors: Vec<ValidationError> = match schema.validate(answers) {
+        Ok(()) => return Ok(()),
+        Err(errors) => errors
+            .filter(|error| !is_inexact(question_list, error))
+            .flat_map(|error| from_schema_error(question_list, &error))
+            .collect(),
+    };
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Whether `error` is a `multipleOf` failure that only floating-point
+/// division produced. The schema engine divides binary floats, rejecting
+/// answers such as 0.07 for a step of 0.01, so the answer is checked again
+/// against the exact `Decimal` step of its question.
+fn is_inexact(question_list: &QuestionList, error: &jsonschema::ValidationError) -> bool {
+    match &error.kind {
+        ValidationErrorKind::MultipleOf { multiple_of } if multiple_of.fract() != 0.0 => {}
+        _ => return false,
+    }
+    let path = error.instance_path.clone().into_vec();
+    let step = match find_question(&question_list.questions, &path) {
+        Some(Question::Number(q)) => q.multiple_of(),
+        _ => None,
+    };
+    let value = match error.instance.as_ref() {
+        Value::Number(n) => answer::decimal(n),
+        _ => None,
+    };
+    match (value, step) {
+        (Some(value), Some(step)) if !step.is_zero() => (value % step).is_zero(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qna::{questionlist, schemacache};
+    use serde_json::json;
+
+    #[test]
+    fn errors_point_at_questions() {
+        let sample = questionlist::sample_string_questions();
+        let compiled = schemacache::compile(&sample).unwrap();
+
+        let answers = json!({
+            "1": "Ice",
+            "8": ["Ice", 2],
+            "88": 110,
+            "222": true,
+            "24": "dork",
+            "899": true,
+            "extra": 1,
+        });
+        let mut errors = validate_with_schema(&sample, &compiled, &answers).unwrap_err();
+        errors.sort_by(|a, b| a.pointer.cmp(&b.pointer));
+
+        let summary: Vec<(&str, Option<i32>, &str)> = errors
+            .iter()
+            .map(|e| (e.pointer.as_str(), e.question_id, e.code))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("/24", Some(24), "enum"),
+                ("/3", Some(3), "required"),
+                ("/8/1", Some(81), "type"),
+                ("/88", Some(88), "maximum"),
+                ("/extra", None, "additionalProperties"),
+            ]
+        );
+        assert_eq!(errors[1].title.as_deref(), Some("Some title 2"));
+        assert_eq!(errors[0].message, "must be one of zork, bork");
+    }
+}
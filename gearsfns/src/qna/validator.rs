@@ -0,0 +1,594 @@
+//! Validation of typed answers that walks the `QuestionList` directly,
+//! without compiling a JSON Schema.
+//!
+//! The rules and the errors they produce match those of the schema that
+//! `questiondto` emits, so either path can be used to check a submission.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use regex::Regex;
+use serde_json::Value;
+use url::Url;
+use uuid::Uuid;
+
+use super::answer::{self, Answer, AnswerSet, Selection};
+use super::dates;
+use super::questionlist::question::{DateBound, TextFormat};
+use super::questionlist::{Question, QuestionList};
+use super::validation::{ValidationError, Violation};
+
+/// Checks `answers` against the limits of every question in `question_list`.
+pub fn validate(
+    question_list: &QuestionList,
+    answers: &AnswerSet,
+) -> Result<(), Vec<ValidationError>> {
+    validate_at(question_list, answers, Utc::now())
+}
+
+/// Like `validate`, resolving relative date bounds as of `now`.
+pub fn validate_at(
+    question_list: &QuestionList,
+    answers: &AnswerSet,
+    now: DateTime<Utc>,
+) -> Result<(), Vec<ValidationError>> {
+    let mut validator = Validator {
+        question_list,
+        now,
+        errors: vec![],
+    };
+    for question in &question_list.questions {
+        let path = vec![question.property_name()];
+        let visible = match question.visible_if() {
+            Some(condition) => condition.holds(
+                answers
+                    .get(condition.question_id)
+                    .and_then(|answer| serde_json::to_value(answer).ok())
+                    .as_ref(),
+            ),
+            None => true,
+        };
+        match answers.get(question.get_id()) {
+            Some(_) if !visible => validator.fail(&path, Violation::Hidden),
+            Some(answer) => validator.answer(question, &path, answer),
+            None if visible && question.is_required() => validator.fail(&path, Violation::Required),
+            None => {}
+        }
+    }
+
+    if validator.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(validator.errors)
+    }
+}
+
+/// Parses submitted answers and validates them in one go.
+pub fn check(
+    question_list: &QuestionList,
+    value: &Value,
+) -> Result<AnswerSet, Vec<ValidationError>> {
+    let answers = answer::parse(question_list, value)?;
+    validate(question_list, &answers)?;
+    Ok(answers)
+}
+
+/// Whether `value` is in `format`, as judged by the schema engine.
+pub fn matches_format(format: TextFormat, value: &str) -> bool {
+    match format {
+        // Only the local part is checked, like the schema engine does.
+        TextFormat::Email => match value.find('@') {
+            Some(at) if at > 0 => {
+                let local = &value[..at];
+                !local.starts_with('.') && !local.ends_with('.') && !local.contains("..")
+            }
+            _ => false,
+        },
+        TextFormat::Uri => Url::parse(value).is_ok(),
+        TextFormat::Date => dates::parse_date(value).is_some(),
+        TextFormat::DateTime => DateTime::parse_from_rfc3339(value).is_ok(),
+        TextFormat::Uuid => Uuid::parse_str(value).is_ok(),
+    }
+}
+
+struct Validator<'a> {
+    question_list: &'a QuestionList,
+    now: DateTime<Utc>,
+    errors: Vec<ValidationError>,
+}
+
+impl Validator<'_> {
+    fn fail(&mut self, path: &[String], violation: Violation) {
+        self.errors
+            .push(ValidationError::new(self.question_list, path, violation));
+    }
+
+    /// The question's timezone, or `None` after reporting a bad one.
+    fn timezone(&mut self, path: &[String], name: Option<&str>) -> Option<Tz> {
+        match dates::timezone(name) {
+            Ok(timezone) => Some(timezone),
+            Err(err) => {
+                self.fail(path, Violation::Other(err));
+                None
+            }
+        }
+    }
+
+    /// Checks `date` against bounds resolved for the current day in `timezone`.
+    fn date_bounds(
+        &mut self,
+        path: &[String],
+        date: NaiveDate,
+        timezone: Tz,
+        bounds: (Option<DateBound>, Option<DateBound>),
+    ) {
+        let today = dates::date_in(&self.now, timezone);
+        let mut resolve = |bound: DateBound| {
+            let date = bound.resolve(today);
+            if date.is_none() {
+                let message = format!("bound {} is not a date that can be represented", bound);
+                self.fail(path, Violation::Other(message));
+            }
+            date
+        };
+        let (min, max) = (
+            bounds.0.and_then(&mut resolve),
+            bounds.1.and_then(&mut resolve),
+        );
+        if let Some(min) = min.filter(|min| date < *min) {
+            self.fail(path, Violation::NotBefore(min.to_string()));
+        }
+        if let Some(max) = max.filter(|max| date > *max) {
+            self.fail(path, Violation::NotAfter(max.to_string()));
+        }
+    }
+
+    fn answer(&mut self, question: &Question, path: &[String], answer: &Answer) {
+        match (question, answer) {
+            (Question::Integer(q), Answer::Integer(value)) => {
+                if let Some(min) = q.min {
+                    if *value < min {
+                        self.fail(path, Violation::Minimum(min.to_string()));
+                    }
+                }
+                if let Some(max) = q.max {
+                    if *value > max {
+                        self.fail(path, Violation::Maximum(max.to_string()));
+                    }
+                }
+                if let Some(step) = q.step.filter(|step| *step != 0) {
+                    if value % step != 0 {
+                        self.fail(path, Violation::MultipleOf(step.to_string()));
+                    }
+                }
+            }
+            (Question::Number(q), Answer::Number(value)) => {
+                if let Some(min) = q.min.filter(|min| value < min) {
+                    self.fail(path, Violation::Minimum(min.to_string()));
+                }
+                if let Some(max) = q.max.filter(|max| value > max) {
+                    self.fail(path, Violation::Maximum(max.to_string()));
+                }
+                if let Some(step) = q.multiple_of().filter(|step| !step.is_zero()) {
+                    if !(*value % step).is_zero() {
+                        self.fail(path, Violation::MultipleOf(step.normalize().to_string()));
+                    }
+                }
+            }
+            (Question::FreeText(q), Answer::FreeText(value)) => {
+                let length = value.chars().count() as i64;
+                if let Some(min_length) = q.min_length {
+                    if length < min_length as i64 {
+                        self.fail(path, Violation::MinLength(min_length.max(0) as u64));
+                    }
+                }
+                if let Some(max_length) = q.max_length {
+                    if length > max_length as i64 {
+                        self.fail(path, Violation::MaxLength(max_length.max(0) as u64));
+                    }
+                }
+                if let Some(pattern) = &q.pattern {
+                    match Regex::new(pattern) {
+                        Ok(regex) if regex.is_match(value) => {}
+                        Ok(_) => self.fail(path, Violation::Pattern(pattern.clone())),
+                        Err(err) => self.fail(path, Violation::Other(err.to_string())),
+                    }
+                }
+                if let Some(format) = q.format {
+                    if !matches_format(format, value) {
+                        self.fail(path, Violation::Format(format.as_str().into()));
+                    }
+                }
+            }
+            (Question::TrueOrFalse(_), Answer::TrueOrFalse(_)) => {}
+            (Question::FixedList(q), Answer::FixedList(Selection::Single(value))) => {
+                if !q.items.contains(value) {
+                    self.fail(path, Violation::Enum(q.items.clone()));
+                }
+            }
+            (Question::FixedList(q), Answer::FixedList(Selection::Multiple(values))) => {
+                for (index, value) in values.iter().enumerate() {
+                    if !q.items.contains(value) {
+                        let mut path = path.to_vec();
+                        path.push(index.to_string());
+                        self.fail(&path, Violation::Enum(q.items.clone()));
+                    }
+                }
+                if values
+                    .iter()
+                    .enumerate()
+                    .any(|(index, value)| values[..index].contains(value))
+                {
+                    self.fail(path, Violation::UniqueItems);
+                }
+                if let Some(min) = q.min_selections {
+                    if (values.len() as i64) < min as i64 {
+                        self.fail(path, Violation::MinItems(min.max(0) as u64));
+                    }
+                }
+                if let Some(max) = q.max_selections {
+                    if values.len() as i64 > max as i64 {
+                        self.fail(path, Violation::MaxItems(max.max(0) as u64));
+                    }
+                }
+            }
+            (Question::ArrayOf(q), Answer::ArrayOf(items)) => {
+                for (index, item) in items.iter().enumerate() {
+                    let mut path = path.to_vec();
+                    path.push(index.to_string());
+                    self.answer(&q.question, &path, item);
+                }
+                if let Some(min) = q.min_items {
+                    if (items.len() as i64) < min as i64 {
+                        self.fail(path, Violation::MinItems(min.max(0) as u64));
+                    }
+                }
+                if let Some(max) = q.max_items {
+                    if items.len() as i64 > max as i64 {
+                        self.fail(path, Violation::MaxItems(max.max(0) as u64));
+                    }
+                }
+            }
+            // Only top-level questions are shown conditionally, so every
+            // required member of a group must be answered.
+            (Question::Group(q), Answer::Group(answers)) => {
+                for member in &q.questions {
+                    let mut path = path.to_vec();
+                    path.push(member.property_name());
+                    match answers.get(member.get_id()) {
+                        Some(answer) => self.answer(member, &path, answer),
+                        None if member.is_required() => self.fail(&path, Violation::Required),
+                        None => {}
+                    }
+                }
+            }
+            (Question::Date(q), Answer::Date(date)) => {
+                if let Some(timezone) = self.timezone(path, q.timezone.as_deref()) {
+                    self.date_bounds(path, *date, timezone, (q.min, q.max));
+                }
+            }
+            (Question::DateTime(q), Answer::DateTime(date_time)) => {
+                if let Some(timezone) = self.timezone(path, q.timezone.as_deref()) {
+                    let date = dates::date_in(date_time, timezone);
+                    self.date_bounds(path, date, timezone, (q.min, q.max));
+                }
+            }
+            (Question::Time(q), Answer::Time(time)) => {
+                if let Some(min) = q.min.filter(|min| time < min) {
+                    self.fail(path, Violation::NotBefore(min.to_string()));
+                }
+                if let Some(max) = q.max.filter(|max| time > max) {
+                    self.fail(path, Violation::NotAfter(max.to_string()));
+                }
+            }
+            // `answer::parse` only builds answers of the matching kind.
+            _ => self.fail(
+                path,
+                Violation::Other("answer does not match its question".into()),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qna::{questionlist, schemacache, validation};
+    use serde_json::json;
+
+    fn summary(errors: &[ValidationError]) -> Vec<(String, &'static str, String)> {
+        let mut summary: Vec<_> = errors
+            .iter()
+            .map(|e| (e.pointer.clone(), e.code, e.message.clone()))
+            .collect();
+        summary.sort();
+        summary
+    }
+
+    #[test]
+    fn accepts_valid_answers() {
+        let sample = questionlist::sample_string_questions();
+        let value = json!({
+            "1": "Ice",
+            "8": ["Ice", "Cream"],
+            "88": 10,
+            "222": true,
+            "24": "zork",
+            "3": "Some text",
+            "899": true,
+        });
+
+        let answers = check(&sample, &value).unwrap();
+        assert_eq!(answers.len(), 7);
+    }
+
+    #[test]
+    fn checks_repeated_groups() {
+        let sample = questionlist::sample_string_questions();
+        let mut value =
+            json!({ "1": "Ice", "8": [], "88": 10, "222": false, "24": "zork", "899": true });
+        value["dependants"] = json!([
+            { "name": "Ann", "age": 7, "relationship": "child" },
+            { "name": "Bob", "age": 40 },
+        ]);
+        let answers = check(&sample, &value).unwrap();
+        match answers.get(30) {
+            Some(Answer::ArrayOf(dependants)) => assert_eq!(dependants.len(), 2),
+            other => panic!("parsed {:?}", other),
+        }
+
+        value["dependants"] = json!(vec![json!({ "name": "Ann", "age": 7 }); 11]);
+        let errors = check(&sample, &value).unwrap_err();
+        assert_eq!(
+            summary(&errors),
+            vec![(
+                "/dependants".to_string(),
+                "maxItems",
+                "must have at most 10 items".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn honours_visibility_conditions() {
+        let sample = questionlist::sample_string_questions();
+        let hidden =
+            json!({ "1": "Ice", "8": [], "88": 10, "222": false, "24": "zork", "899": true });
+        assert!(check(&sample, &hidden).is_ok());
+
+        let mut answered = hidden.clone();
+        answered["3"] = json!("Some text");
+        let errors = check(&sample, &answered).unwrap_err();
+        assert_eq!(summary(&errors)[0].0, "/3");
+        assert_eq!(errors[0].code, "hidden");
+
+        answered["222"] = json!(true);
+        assert!(check(&sample, &answered).is_ok());
+    }
+
+    #[test]
+    fn checks_patterns_and_formats() {
+        let mut sample = questionlist::sample_string_questions();
+        if let Question::FreeText(q) = &mut sample.questions[0] {
+            q.pattern = Some("^[A-Z]".into());
+        }
+        if let Question::FreeText(q) = &mut sample.questions[5] {
+            q.format = Some(TextFormat::Email);
+        }
+        let compiled = schemacache::compile(&sample).unwrap();
+
+        let value = json!({ "1": "ice", "8": [], "88": 10, "222": true, "24": "zork", "3": "..@x", "899": true });
+        let native = check(&sample, &value).unwrap_err();
+        assert_eq!(
+            summary(&native),
+            summary(&validation::validate_with_schema(&sample, &compiled, &value).unwrap_err())
+        );
+        assert_eq!(
+            summary(&native),
+            vec![
+                (
+                    "/1".to_string(),
+                    "pattern",
+                    "must match the pattern ^[A-Z]".to_string()
+                ),
+                (
+                    "/3".to_string(),
+                    "format",
+                    "must be a valid email".to_string()
+                ),
+            ]
+        );
+
+        for format in &[
+            TextFormat::Uri,
+            TextFormat::Date,
+            TextFormat::DateTime,
+            TextFormat::Uuid,
+        ] {
+            if let Question::FreeText(q) = &mut sample.questions[5] {
+                q.format = Some(*format);
+            }
+            let compiled = schemacache::compile(&sample).unwrap();
+            for text in &[
+                "https://example.com/a",
+                "2020-02-30",
+                "2020-2-01",
+                "2020-02-29",
+                "2020-02-29T12:00:00Z",
+                "8f1b3c4e-9d2a-4f5b-8c7d-6e5f4a3b2c1d",
+                "nope",
+            ] {
+                let value = json!({ "1": "Ice", "8": [], "88": 10, "222": true, "24": "zork", "3": text, "899": true });
+                assert_eq!(
+                    check(&sample, &value).is_ok(),
+                    validation::validate_with_schema(&sample, &compiled, &value).is_ok(),
+                    "{} as {}",
+                    text,
+                    format.as_str()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn enforces_date_and_time_bounds() {
+        let question_list: QuestionList = serde_json::from_value(json!({
+            "title": "Appointments",
+            "description": "",
+            "questions": [
+                { "Date": { "id": 1, "title": "Born", "description": "", "required": true,
+                    "visible_if": null, "default": null, "min": "1900-01-01", "max": "today-18y",
+                    "timezone": "Australia/Brisbane" } },
+                { "DateTime": { "id": 2, "title": "Slot", "description": "", "required": true,
+                    "visible_if": null, "default": null, "min": "today", "max": "today+30d",
+                    "timezone": "Australia/Brisbane" } },
+                { "Time": { "id": 3, "title": "Call me at", "description": "", "required": false,
+                    "visible_if": null, "default": null, "min": "09:00:00", "max": "17:00:00" } },
+            ],
+        }))
+        .unwrap();
+        // Already 2024-01-02 in Brisbane.
+        let now: DateTime<Utc> = "2024-01-01T20:00:00Z".parse().unwrap();
+        let validate = |value: serde_json::Value| {
+            let answers = answer::parse(&question_list, &value)?;
+            validate_at(&question_list, &answers, now)
+        };
+
+        assert!(validate(json!({
+            "1": "2006-01-02",
+            "2": "2024-01-01T14:00:00Z",
+            "3": "09:30",
+        }))
+        .is_ok());
+
+        let errors = validate(json!({
+            "1": "2006-01-03",
+            "2": "2024-01-01T13:00:00Z",
+            "3": "17:30",
+        }))
+        .unwrap_err();
+        assert_eq!(
+            summary(&errors),
+            vec![
+                (
+                    "/1".into(),
+                    "formatMaximum",
+                    "must not be after 2006-01-02".into()
+                ),
+                (
+                    "/2".into(),
+                    "formatMinimum",
+                    "must not be before 2024-01-02".into()
+                ),
+                (
+                    "/3".into(),
+                    "formatMaximum",
+                    "must not be after 17:00:00".into()
+                ),
+            ]
+        );
+
+        let errors = validate(json!({ "1": "2006-1-3", "2": "soon", "3": "5pm" })).unwrap_err();
+        let codes: Vec<&str> = errors.iter().map(|e| e.code).collect();
+        assert_eq!(codes, vec!["format", "format", "pattern"]);
+
+        let mut far_off = question_list.clone();
+        if let Question::Date(q) = &mut far_off.questions[0] {
+            q.max = Some(DateBound::Today(i32::MAX, dates::Unit::Days));
+        }
+        let answers = answer::parse(
+            &far_off,
+            &json!({ "1": "2006-01-02", "2": "2024-01-01T14:00:00Z" }),
+        );
+        let errors = validate_at(&far_off, &answers.unwrap(), now).unwrap_err();
+        assert_eq!(errors[0].pointer, "/1");
+        assert_eq!(errors[0].code, "invalid");
+    }
+
+    #[test]
+    fn checks_numbers_exactly() {
+        let question_list: QuestionList = serde_json::from_value(json!({
+            "title": "Shopping",
+            "description": "",
+            "questions": [
+                { "Number": { "id": 1, "title": "Price", "description": "", "required": true,
+                    "visible_if": null, "default": null, "precision": 2, "step": null,
+                    "min": 0, "max": 1000, "unit": "AUD" } },
+                { "Number": { "id": 2, "title": "Weight", "description": "", "required": false,
+                    "visible_if": null, "default": null, "precision": null, "step": 0.25,
+                    "min": 0.5, "max": null, "unit": "kg" } },
+            ],
+        }))
+        .unwrap();
+        let compiled = schemacache::compile(&question_list).unwrap();
+
+        // Floating-point division makes 0.07 / 0.01 and 0.29 / 0.01 inexact.
+        for price in &[json!(0.07), json!(0.29), json!(19.99), json!(1000)] {
+            let value = json!({ "1": price, "2": 1.75 });
+            assert!(check(&question_list, &value).is_ok(), "{}", price);
+            assert!(validation::validate_with_schema(&question_list, &compiled, &value).is_ok());
+        }
+        // Answers off the step stay rejected on the schema path too.
+        for price in &[json!(0.005), json!(0.075)] {
+            let value = json!({ "1": price });
+            let errors =
+                validation::validate_with_schema(&question_list, &compiled, &value).unwrap_err();
+            assert_eq!(errors[0].code, "multipleOf", "{}", price);
+        }
+
+        // Fifteen significant digits survive storage; more are refused.
+        let answers = check(&question_list, &json!({ "1": 10, "2": 1234567890123.25 })).unwrap();
+        assert_eq!(
+            answers.to_json(&question_list.questions)["2"],
+            json!(1234567890123.25)
+        );
+        let errors =
+            check(&question_list, &json!({ "1": 10, "2": 12345678901234.25 })).unwrap_err();
+        assert_eq!(errors[0].code, "invalid");
+
+        let errors = check(&question_list, &json!({ "1": 12.345, "2": 0.3 })).unwrap_err();
+        assert_eq!(
+            summary(&errors),
+            vec![
+                (
+                    "/1".to_string(),
+                    "multipleOf",
+                    "must be a multiple of 0.01".to_string()
+                ),
+                (
+                    "/2".to_string(),
+                    "minimum",
+                    "must be at least 0.5".to_string()
+                ),
+                (
+                    "/2".to_string(),
+                    "multipleOf",
+                    "must be a multiple of 0.25".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn matches_schema_errors() {
+        let sample = questionlist::sample_string_questions();
+        let compiled = schemacache::compile(&sample).unwrap();
+
+        let submissions = vec![
+            json!({ "1": "Ice", "88": 110, "24": "dork" }),
+            json!({ "1": "x".repeat(65), "8": ["", "y".repeat(65)], "88": -5, "3": "" }),
+            json!({ "1": "Ice", "8": [], "88": 15, "222": false, "24": "bork", "3": "ok" }),
+            json!({ "1": "Ice", "8": [], "88": 10, "222": true, "24": "bork", "899": true }),
+            json!({ "1": "Ice", "8": [], "88": 10, "24": "zork", "899": true, "25": [] }),
+            json!({ "1": "Ice", "8": [], "88": 10, "24": "zork", "899": true, "25": ["nuts", "nuts", "gravy"] }),
+            json!({ "1": "Ice", "8": [], "88": 10, "222": false, "24": "zork", "899": true,
+                "dependants": [{ "name": "", "age": 200, "relationship": "cousin" }, { "age": 3 }] }),
+            json!({ "1": "Ice", "8": [], "88": 10, "222": false, "24": "zork", "899": true, "dependants": ["Ann"] }),
+        ];
+        for value in &submissions {
+            let from_schema =
+                validation::validate_with_schema(&sample, &compiled, value).unwrap_err();
+            let native = check(&sample, value).unwrap_err();
+            assert_eq!(summary(&native), summary(&from_schema), "{}", value);
+        }
+    }
+}
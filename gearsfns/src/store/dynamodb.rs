@@ -0,0 +1,574 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rusoto_core::RusotoError;
+use rusoto_dynamodb::{
+    AttributeValue, DeleteItemInput, DynamoDb, DynamoDbClient, GetItemInput, PutItemError,
+    PutItemInput, QueryInput, ScanInput,
+};
+use uuid::Uuid;
+
+use super::{
+    backend, region_from_env, required_env, QuestionListRepository, Revision, RevisionStore,
+    Status, StoreError, Submission, SubmissionStore,
+};
+use crate::qna::questionlist::QuestionList;
+
+/// Environment variable pointing the DynamoDB client at DynamoDB Local.
+pub const ENDPOINT_VAR: &str = "DYNAMODB_ENDPOINT";
+
+fn string_value(value: impl Into<String>) -> AttributeValue {
+    AttributeValue {
+        s: Some(value.into()),
+        ..Default::default()
+    }
+}
+
+fn number_value(value: u32) -> AttributeValue {
+    AttributeValue {
+        n: Some(value.to_string()),
+        ..Default::default()
+    }
+}
+
+fn document(item: Option<HashMap<String, AttributeValue>>) -> Option<String> {
+    item.and_then(|mut item| item.remove("document"))
+        .and_then(|document| document.s)
+}
+
+/// Stores each questionnaire as a JSON document under the `document`
+/// attribute of an item keyed by `id`.
+pub struct DynamoDbQuestionListRepository {
+    client: DynamoDbClient,
+    table_name: String,
+}
+
+impl DynamoDbQuestionListRepository {
+    pub fn new(client: DynamoDbClient, table_name: impl Into<String>) -> Self {
+        Self {
+            client,
+            table_name: table_name.into(),
+        }
+    }
+
+    /// Builds a repository for the table named by `QUESTIONLIST_TABLE`.
+    pub fn from_env() -> Result<Self, StoreError> {
+        Ok(Self::new(
+            DynamoDbClient::new(region_from_env(ENDPOINT_VAR)),
+            required_env("QUESTIONLIST_TABLE")?,
+        ))
+    }
+
+    fn key(id: &str) -> HashMap<String, AttributeValue> {
+        let mut key = HashMap::new();
+        key.insert("id".to_string(), string_value(id));
+        key
+    }
+}
+
+#[async_trait]
+impl QuestionListRepository for DynamoDbQuestionListRepository {
+    async fn get(&self, id: &str) -> Result<Option<QuestionList>, StoreError> {
+        let output = self
+            .client
+            .get_item(GetItemInput {
+                table_name: self.table_name.clone(),
+                key: Self::key(id),
+                consistent_read: Some(true),
+                ..Default::default()
+            })
+            .await
+            .map_err(backend)?;
+
+        match document(output.item) {
+            Some(document) => Ok(Some(serde_json::from_str(&document)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn put(&self, id: &str, question_list: &QuestionList) -> Result<(), StoreError> {
+        let mut item = Self::key(id);
+        item.insert(
+            "document".to_string(),
+            string_value(serde_json::to_string(question_list)?),
+        );
+        self.client
+            .put_item(PutItemInput {
+                table_name: self.table_name.clone(),
+                item,
+                ..Default::default()
+            })
+            .await
+            .map_err(backend)?;
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<String>, StoreError> {
+        let mut names = HashMap::new();
+        names.insert("#id".to_string(), "id".to_string());
+
+        let mut ids = Vec::new();
+        let mut exclusive_start_key = None;
+        loop {
+            let output = self
+                .client
+                .scan(ScanInput {
+                    table_name: self.table_name.clone(),
+                    projection_expression: Some("#id".into()),
+                    expression_attribute_names: Some(names.clone()),
+                    exclusive_start_key,
+                    ..Default::default()
+                })
+                .await
+                .map_err(backend)?;
+
+            ids.extend(
+                output
+                    .items
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|mut item| item.remove("id").and_then(|id| id.s)),
+            );
+
+            match output.last_evaluated_key {
+                Some(key) if !key.is_empty() => exclusive_start_key = Some(key),
+                _ => break,
+            }
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), StoreError> {
+        self.client
+            .delete_item(DeleteItemInput {
+                table_name: self.table_name.clone(),
+                key: Self::key(id),
+                ..Default::default()
+            })
+            .await
+            .map_err(backend)?;
+        Ok(())
+    }
+}
+
+/// Stores each revision as a JSON document under the `document` attribute of
+/// an item keyed by `questionnaire_id` (hash) and the numeric `version`
+/// (range), with its `status` alongside so writes can be made conditional
+/// on it.
+pub struct DynamoDbRevisionStore {
+    client: DynamoDbClient,
+    table_name: String,
+}
+
+impl DynamoDbRevisionStore {
+    pub fn new(client: DynamoDbClient, table_name: impl Into<String>) -> Self {
+        Self {
+            client,
+            table_name: table_name.into(),
+        }
+    }
+
+    /// Builds a store for the table named by `REVISION_TABLE`.
+    pub fn from_env() -> Result<Self, StoreError> {
+        Ok(Self::new(
+            DynamoDbClient::new(region_from_env(ENDPOINT_VAR)),
+            required_env("REVISION_TABLE")?,
+        ))
+    }
+
+    fn key(questionnaire_id: &str, version: u32) -> HashMap<String, AttributeValue> {
+        let mut key = HashMap::new();
+        key.insert(
+            "questionnaire_id".to_string(),
+            string_value(questionnaire_id),
+        );
+        key.insert("version".to_string(), number_value(version));
+        key
+    }
+
+    /// Writes `revision` provided `condition` holds for the item it replaces.
+    async fn put_if(
+        &self,
+        revision: &Revision,
+        condition: &str,
+        values: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<(), StoreError> {
+        let status = match revision.status {
+            Status::Draft => "draft",
+            Status::Published => "published",
+        };
+        let mut item = Self::key(&revision.questionnaire_id, revision.version);
+        item.insert("status".to_string(), string_value(status));
+        item.insert(
+            "document".to_string(),
+            string_value(serde_json::to_string(revision)?),
+        );
+        let mut names = HashMap::new();
+        names.insert("#version".to_string(), "version".to_string());
+        names.insert("#status".to_string(), "status".to_string());
+        self.client
+            .put_item(PutItemInput {
+                table_name: self.table_name.clone(),
+                item,
+                condition_expression: Some(condition.into()),
+                expression_attribute_names: Some(names),
+                expression_attribute_values: values,
+                ..Default::default()
+            })
+            .await
+            .map_err(|err| match err {
+                RusotoError::Service(PutItemError::ConditionalCheckFailed(_)) => {
+                    StoreError::Conflict(format!(
+                        "revision {} of {} cannot be written as {}",
+                        revision.version, revision.questionnaire_id, status
+                    ))
+                }
+                err => backend(err),
+            })?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RevisionStore for DynamoDbRevisionStore {
+    async fn create(&self, revision: &Revision) -> Result<(), StoreError> {
+        self.put_if(
+            revision,
+            "attribute_not_exists(#version) AND attribute_not_exists(#status)",
+            None,
+        )
+        .await
+    }
+
+    async fn get(
+        &self,
+        questionnaire_id: &str,
+        version: u32,
+    ) -> Result<Option<Revision>, StoreError> {
+        let output = self
+            .client
+            .get_item(GetItemInput {
+                table_name: self.table_name.clone(),
+                key: Self::key(questionnaire_id, version),
+                consistent_read: Some(true),
+                ..Default::default()
+            })
+            .await
+            .map_err(backend)?;
+
+        match document(output.item) {
+            Some(document) => Ok(Some(serde_json::from_str(&document)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn list(&self, questionnaire_id: &str) -> Result<Vec<Revision>, StoreError> {
+        let mut values = HashMap::new();
+        values.insert(":id".to_string(), string_value(questionnaire_id));
+
+        let mut revisions = Vec::new();
+        let mut exclusive_start_key = None;
+        loop {
+            let output = self
+                .client
+                .query(QueryInput {
+                    table_name: self.table_name.clone(),
+                    key_condition_expression: Some("questionnaire_id = :id".into()),
+                    expression_attribute_values: Some(values.clone()),
+                    consistent_read: Some(true),
+                    scan_index_forward: Some(true),
+                    exclusive_start_key,
+                    ..Default::default()
+                })
+                .await
+                .map_err(backend)?;
+
+            for item in output.items.unwrap_or_default() {
+                if let Some(document) = document(Some(item)) {
+                    revisions.push(serde_json::from_str(&document)?);
+                }
+            }
+
+            match output.last_evaluated_key {
+                Some(key) if !key.is_empty() => exclusive_start_key = Some(key),
+                _ => break,
+            }
+        }
+        Ok(revisions)
+    }
+
+    async fn publish(
+        &self,
+        questionnaire_id: &str,
+        version: u32,
+        at: DateTime<Utc>,
+    ) -> Result<(), StoreError> {
+        let mut revision = match self.get(questionnaire_id, version).await? {
+            Some(revision) if revision.status == Status::Draft => revision,
+            _ => {
+                return Err(StoreError::Conflict(format!(
+                    "revision {} of {} is not a draft",
+                    version, questionnaire_id
+                )))
+            }
+        };
+        revision.status = Status::Published;
+        revision.published_at = Some(at);
+        let mut values = HashMap::new();
+        values.insert(":draft".to_string(), string_value("draft"));
+        // Only the status changes; the condition keeps a concurrent publish
+        // from stamping the revision twice.
+        self.put_if(
+            &revision,
+            "attribute_exists(#version) AND #status = :draft",
+            Some(values),
+        )
+        .await
+    }
+}
+
+/// Stores each submission as a JSON document under the `document` attribute
+/// of an item keyed by `questionnaire_id` (hash) and `id` (range).
+pub struct DynamoDbSubmissionStore {
+    client: DynamoDbClient,
+    table_name: String,
+}
+
+impl DynamoDbSubmissionStore {
+    pub fn new(client: DynamoDbClient, table_name: impl Into<String>) -> Self {
+        Self {
+            client,
+            table_name: table_name.into(),
+        }
+    }
+
+    /// Builds a store for the table named by `SUBMISSION_TABLE`.
+    pub fn from_env() -> Result<Self, StoreError> {
+        Ok(Self::new(
+            DynamoDbClient::new(region_from_env(ENDPOINT_VAR)),
+            required_env("SUBMISSION_TABLE")?,
+        ))
+    }
+
+    fn key(questionnaire_id: &str, id: Uuid) -> HashMap<String, AttributeValue> {
+        let mut key = HashMap::new();
+        key.insert(
+            "questionnaire_id".to_string(),
+            string_value(questionnaire_id),
+        );
+        key.insert("id".to_string(), string_value(id.to_string()));
+        key
+    }
+}
+
+#[async_trait]
+impl SubmissionStore for DynamoDbSubmissionStore {
+    async fn put(&self, submission: &Submission) -> Result<(), StoreError> {
+        let mut item = Self::key(&submission.questionnaire_id, submission.id);
+        item.insert(
+            "document".to_string(),
+            string_value(serde_json::to_string(submission)?),
+        );
+        self.client
+            .put_item(PutItemInput {
+                table_name: self.table_name.clone(),
+                item,
+                ..Default::default()
+            })
+            .await
+            .map_err(backend)?;
+        Ok(())
+    }
+
+    async fn get(
+        &self,
+        questionnaire_id: &str,
+        id: Uuid,
+    ) -> Result<Option<Submission>, StoreError> {
+        let output = self
+            .client
+            .get_item(GetItemInput {
+                table_name: self.table_name.clone(),
+                key: Self::key(questionnaire_id, id),
+                consistent_read: Some(true),
+                ..Default::default()
+            })
+            .await
+            .map_err(backend)?;
+
+        match document(output.item) {
+            Some(document) => Ok(Some(serde_json::from_str(&document)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn list(&self, questionnaire_id: &str) -> Result<Vec<Submission>, StoreError> {
+        let mut values = HashMap::new();
+        values.insert(":id".to_string(), string_value(questionnaire_id));
+
+        let mut submissions: Vec<Submission> = Vec::new();
+        let mut exclusive_start_key = None;
+        loop {
+            let output = self
+                .client
+                .query(QueryInput {
+                    table_name: self.table_name.clone(),
+                    key_condition_expression: Some("questionnaire_id = :id".into()),
+                    expression_attribute_values: Some(values.clone()),
+                    consistent_read: Some(true),
+                    exclusive_start_key,
+                    ..Default::default()
+                })
+                .await
+                .map_err(backend)?;
+
+            for item in output.items.unwrap_or_default() {
+                if let Some(document) = document(Some(item)) {
+                    submissions.push(serde_json::from_str(&document)?);
+                }
+            }
+
+            match output.last_evaluated_key {
+                Some(key) if !key.is_empty() => exclusive_start_key = Some(key),
+                _ => break,
+            }
+        }
+        // The range key is the submission id, which says nothing of when it
+        // was made.
+        submissions.sort_by_key(|s| s.submitted_at);
+        Ok(submissions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qna::questionlist;
+    use rusoto_dynamodb::{AttributeDefinition, CreateTableInput, KeySchemaElement};
+    use serde_json::json;
+
+    /// Creates a table keyed by `(name, attribute type, key type)` triples.
+    async fn create_table(
+        client: &DynamoDbClient,
+        prefix: &str,
+        keys: &[(&str, &str, &str)],
+    ) -> String {
+        let table_name = format!("{}-{}", prefix, Uuid::new_v4());
+        client
+            .create_table(CreateTableInput {
+                table_name: table_name.clone(),
+                attribute_definitions: keys
+                    .iter()
+                    .map(|(name, attribute_type, _)| AttributeDefinition {
+                        attribute_name: name.to_string(),
+                        attribute_type: attribute_type.to_string(),
+                    })
+                    .collect(),
+                key_schema: keys
+                    .iter()
+                    .map(|(name, _, key_type)| KeySchemaElement {
+                        attribute_name: name.to_string(),
+                        key_type: key_type.to_string(),
+                    })
+                    .collect(),
+                billing_mode: Some("PAY_PER_REQUEST".into()),
+                ..Default::default()
+            })
+            .await
+            .expect("create table");
+        table_name
+    }
+
+    // Needs DynamoDB Local, e.g.
+    // `docker run -p 8000:8000 amazon/dynamodb-local` and
+    // `DYNAMODB_ENDPOINT=http://localhost:8000 cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn round_trip_against_dynamodb_local() {
+        let client = DynamoDbClient::new(region_from_env(ENDPOINT_VAR));
+        let table_name = create_table(&client, "questionlists", &[("id", "S", "HASH")]).await;
+
+        let repo = DynamoDbQuestionListRepository::new(client, table_name);
+        let sample = questionlist::sample_string_questions();
+
+        repo.put("sample", &sample).await.unwrap();
+        assert_eq!(repo.list().await.unwrap(), vec!["sample"]);
+        assert_eq!(
+            repo.get("sample").await.unwrap().unwrap().questions.len(),
+            sample.questions.len()
+        );
+
+        repo.delete("sample").await.unwrap();
+        assert!(repo.get("sample").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn submission_round_trip_against_dynamodb_local() {
+        let client = DynamoDbClient::new(region_from_env(ENDPOINT_VAR));
+        let table_name = create_table(
+            &client,
+            "submissions",
+            &[("questionnaire_id", "S", "HASH"), ("id", "S", "RANGE")],
+        )
+        .await;
+
+        let store = DynamoDbSubmissionStore::new(client, table_name);
+        let submission = Submission::new("sample", 1, json!({ "3": "Ice" }));
+
+        store.put(&submission).await.unwrap();
+        assert_eq!(
+            store.get("sample", submission.id).await.unwrap(),
+            Some(submission.clone())
+        );
+        assert_eq!(store.list("sample").await.unwrap(), vec![submission]);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn revision_round_trip_against_dynamodb_local() {
+        let client = DynamoDbClient::new(region_from_env(ENDPOINT_VAR));
+        let table_name = create_table(
+            &client,
+            "revisions",
+            &[("questionnaire_id", "S", "HASH"), ("version", "N", "RANGE")],
+        )
+        .await;
+
+        let store = DynamoDbRevisionStore::new(client, table_name);
+        let mut sample = questionlist::sample_string_questions();
+        for version in &[2, 10, 1] {
+            sample.version = *version;
+            store
+                .create(&Revision::draft("sample", sample.clone()))
+                .await
+                .unwrap();
+        }
+        let versions: Vec<u32> = store
+            .list("sample")
+            .await
+            .unwrap()
+            .iter()
+            .map(|revision| revision.version)
+            .collect();
+        assert_eq!(versions, vec![1, 2, 10]);
+        assert!(matches!(
+            store.create(&Revision::draft("sample", sample)).await,
+            Err(StoreError::Conflict(_))
+        ));
+
+        store.publish("sample", 2, Utc::now()).await.unwrap();
+        assert!(store
+            .get("sample", 2)
+            .await
+            .unwrap()
+            .unwrap()
+            .is_published());
+        assert!(matches!(
+            store.publish("sample", 2, Utc::now()).await,
+            Err(StoreError::Conflict(_))
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+
+use super::{QuestionListRepository, StoreError};
+use crate::qna::questionlist::QuestionList;
+
+/// File extensions questionnaires are read from, in order of preference.
+const EXTENSIONS: &[&str] = &["json", "yaml", "yml"];
+
+fn io_error(path: &Path, err: io::Error) -> StoreError {
+    StoreError::Backend(format!("{}: {}", path.display(), err))
+}
+
+/// Keeps questionnaires as `{id}.json`, `{id}.yaml` or `{id}.yml` files in a
+/// directory, read afresh on every request so edits show up without a
+/// restart; used by the local development server.
+///
+/// Files are read with blocking calls, which is fine for a single developer
+/// but not for serving traffic.
+#[derive(Debug, Clone)]
+pub struct FileQuestionListRepository {
+    dir: PathBuf,
+}
+
+impl FileQuestionListRepository {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The file holding questionnaire `id`, if there is one. Invalid ids
+    /// never match, so they cannot reach outside the directory.
+    fn path(&self, id: &str) -> Option<PathBuf> {
+        if !is_valid_id(id) {
+            return None;
+        }
+        EXTENSIONS
+            .iter()
+            .map(|extension| self.dir.join(format!("{}.{}", id, extension)))
+            .find(|path| path.is_file())
+    }
+}
+
+/// Whether `id` is a plain file name, naming a file in the directory itself.
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && !id.starts_with('.') && !id.contains(&['/', '\\'][..])
+}
+
+fn is_yaml(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension != "json")
+}
+
+#[async_trait]
+impl QuestionListRepository for FileQuestionListRepository {
+    async fn get(&self, id: &str) -> Result<Option<QuestionList>, StoreError> {
+        let path = match self.path(id) {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        let text = fs::read_to_string(&path).map_err(|err| io_error(&path, err))?;
+        let question_list = if is_yaml(&path) {
+            serde_yaml::from_str(&text)
+                .map_err(|err| StoreError::Backend(format!("{}: {}", path.display(), err)))?
+        } else {
+            serde_json::from_str(&text)?
+        };
+        Ok(Some(question_list))
+    }
+
+    /// Rewrites the file already holding `id` in its own format, or creates
+    /// `{id}.json`. Invalid ids are refused rather than written elsewhere.
+    async fn put(&self, id: &str, question_list: &QuestionList) -> Result<(), StoreError> {
+        if !is_valid_id(id) {
+            return Err(StoreError::Backend(format!(
+                "{:?} is not a valid questionnaire id",
+                id
+            )));
+        }
+        let path = match self.path(id) {
+            Some(path) => path,
+            None => self.dir.join(format!("{}.json", id)),
+        };
+        let text = if is_yaml(&path) {
+            serde_yaml::to_string(question_list)
+                .map_err(|err| StoreError::Backend(format!("{}: {}", path.display(), err)))?
+        } else {
+            serde_json::to_string_pretty(question_list)?
+        };
+        fs::write(&path, text).map_err(|err| io_error(&path, err))
+    }
+
+    async fn list(&self) -> Result<Vec<String>, StoreError> {
+        let entries = fs::read_dir(&self.dir).map_err(|err| io_error(&self.dir, err))?;
+        let mut ids = vec![];
+        for entry in entries {
+            let path = entry.map_err(|err| io_error(&self.dir, err))?.path();
+            let known = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| EXTENSIONS.contains(&extension));
+            if let (true, Some(id)) = (known, path.file_stem().and_then(|stem| stem.to_str())) {
+                ids.push(id.to_string());
+            }
+        }
+        ids.sort();
+        ids.dedup();
+        Ok(ids)
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), StoreError> {
+        match self.path(id) {
+            Some(path) => fs::remove_file(&path).map_err(|err| io_error(&path, err)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qna::questionlist;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn reads_json_and_yaml_files() {
+        let dir = std::env::temp_dir().join(format!("gears-files-{}", Uuid::new_v4()));
+        fs::create_dir(&dir).unwrap();
+        let repo = FileQuestionListRepository::new(&dir);
+        let sample = questionlist::sample_string_questions();
+
+        repo.put("b", &sample).await.unwrap();
+        fs::write(dir.join("a.yaml"), serde_yaml::to_string(&sample).unwrap()).unwrap();
+        fs::write(dir.join("notes.txt"), "not a questionnaire").unwrap();
+        assert_eq!(repo.list().await.unwrap(), vec!["a", "b"]);
+        assert_eq!(repo.get("a").await.unwrap().unwrap().title, sample.title);
+        assert_eq!(repo.get("b").await.unwrap().unwrap().questions.len(), 9);
+        assert!(repo.get("../b").await.unwrap().is_none());
+        for id in &["../escaped", "sub/dir", ".hidden", ""] {
+            assert!(repo.put(id, &sample).await.is_err(), "{}", id);
+        }
+        assert!(!dir.parent().unwrap().join("escaped.json").exists());
+
+        repo.delete("a").await.unwrap();
+        assert!(repo.get("a").await.unwrap().is_none());
+        assert_eq!(repo.list().await.unwrap(), vec!["b"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::{BTreeMap, HashMap};
+use std::iter::FromIterator;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::{
+    QuestionListRepository, Revision, RevisionStore, Status, StoreError, Submission,
+    SubmissionStore,
+};
+use crate::qna::questionlist::QuestionList;
+
+fn poisoned<T>(_: T) -> StoreError {
+    StoreError::Backend("in-memory store lock poisoned".into())
+}
+
+/// Keeps questionnaires in process memory; used by tests and local tooling.
+#[derive(Debug, Default)]
+pub struct MemoryQuestionListRepository {
+    question_lists: RwLock<HashMap<String, QuestionList>>,
+}
+
+impl MemoryQuestionListRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl FromIterator<(String, QuestionList)> for MemoryQuestionListRepository {
+    fn from_iter<I: IntoIterator<Item = (String, QuestionList)>>(iter: I) -> Self {
+        Self {
+            question_lists: RwLock::new(iter.into_iter().collect()),
+        }
+    }
+}
+
+#[async_trait]
+impl QuestionListRepository for MemoryQuestionListRepository {
+    async fn get(&self, id: &str) -> Result<Option<QuestionList>, StoreError> {
+        let question_lists = self.question_lists.read().map_err(poisoned)?;
+        Ok(question_lists.get(id).cloned())
+    }
+
+    async fn put(&self, id: &str, question_list: &QuestionList) -> Result<(), StoreError> {
+        let mut question_lists = self.question_lists.write().map_err(poisoned)?;
+        question_lists.insert(id.to_string(), question_list.clone());
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<String>, StoreError> {
+        let question_lists = self.question_lists.read().map_err(poisoned)?;
+        let mut ids: Vec<String> = question_lists.keys().cloned().collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), StoreError> {
+        let mut question_lists = self.question_lists.write().map_err(poisoned)?;
+        question_lists.remove(id);
+        Ok(())
+    }
+}
+
+/// Keeps revisions in process memory; used by tests and local tooling.
+#[derive(Debug, Default)]
+pub struct MemoryRevisionStore {
+    revisions: RwLock<BTreeMap<(String, u32), Revision>>,
+}
+
+impl MemoryRevisionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RevisionStore for MemoryRevisionStore {
+    async fn create(&self, revision: &Revision) -> Result<(), StoreError> {
+        let mut revisions = self.revisions.write().map_err(poisoned)?;
+        let key = (revision.questionnaire_id.clone(), revision.version);
+        if revisions.contains_key(&key) {
+            return Err(StoreError::Conflict(format!(
+                "revision {} of {} already exists",
+                revision.version, revision.questionnaire_id
+            )));
+        }
+        revisions.insert(key, revision.clone());
+        Ok(())
+    }
+
+    async fn get(
+        &self,
+        questionnaire_id: &str,
+        version: u32,
+    ) -> Result<Option<Revision>, StoreError> {
+        let revisions = self.revisions.read().map_err(poisoned)?;
+        Ok(revisions
+            .get(&(questionnaire_id.to_string(), version))
+            .cloned())
+    }
+
+    async fn list(&self, questionnaire_id: &str) -> Result<Vec<Revision>, StoreError> {
+        let revisions = self.revisions.read().map_err(poisoned)?;
+        Ok(revisions
+            .range((questionnaire_id.to_string(), 0)..=(questionnaire_id.to_string(), u32::MAX))
+            .map(|(_, revision)| revision.clone())
+            .collect())
+    }
+
+    async fn publish(
+        &self,
+        questionnaire_id: &str,
+        version: u32,
+        at: DateTime<Utc>,
+    ) -> Result<(), StoreError> {
+        let mut revisions = self.revisions.write().map_err(poisoned)?;
+        match revisions.get_mut(&(questionnaire_id.to_string(), version)) {
+            Some(revision) if revision.status == Status::Draft => {
+                revision.status = Status::Published;
+                revision.published_at = Some(at);
+                Ok(())
+            }
+            _ => Err(StoreError::Conflict(format!(
+                "revision {} of {} is not a draft",
+                version, questionnaire_id
+            ))),
+        }
+    }
+}
+
+/// Keeps submissions in process memory; used by tests and local tooling.
+#[derive(Debug, Default)]
+pub struct MemorySubmissionStore {
+    submissions: RwLock<HashMap<Uuid, Submission>>,
+}
+
+impl MemorySubmissionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SubmissionStore for MemorySubmissionStore {
+    async fn put(&self, submission: &Submission) -> Result<(), StoreError> {
+        let mut submissions = self.submissions.write().map_err(poisoned)?;
+        submissions.insert(submission.id, submission.clone());
+        Ok(())
+    }
+
+    async fn get(
+        &self,
+        questionnaire_id: &str,
+        id: Uuid,
+    ) -> Result<Option<Submission>, StoreError> {
+        let submissions = self.submissions.read().map_err(poisoned)?;
+        Ok(submissions
+            .get(&id)
+            .filter(|s| s.questionnaire_id == questionnaire_id)
+            .cloned())
+    }
+
+    async fn list(&self, questionnaire_id: &str) -> Result<Vec<Submission>, StoreError> {
+        let submissions = self.submissions.read().map_err(poisoned)?;
+        let mut list: Vec<Submission> = submissions
+            .values()
+            .filter(|s| s.questionnaire_id == questionnaire_id)
+            .cloned()
+            .collect();
+        list.sort_by_key(|s| s.submitted_at);
+        Ok(list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qna::questionlist;
+
+    #[tokio::test]
+    async fn put_get_list_delete() {
+        let repo = MemoryQuestionListRepository::new();
+        let sample = questionlist::sample_string_questions();
+
+        repo.put("b", &sample).await.unwrap();
+        repo.put("a", &sample).await.unwrap();
+        assert_eq!(repo.list().await.unwrap(), vec!["a", "b"]);
+        assert_eq!(repo.get("a").await.unwrap().unwrap().title, sample.title);
+
+        repo.delete("a").await.unwrap();
+        assert!(repo.get("a").await.unwrap().is_none());
+        assert_eq!(repo.list().await.unwrap(), vec!["b"]);
+    }
+
+    #[tokio::test]
+    async fn revisions_are_immutable() {
+        let store = MemoryRevisionStore::new();
+        let mut sample = questionlist::sample_string_questions();
+        sample.version = 1;
+        store
+            .create(&Revision::draft("a", sample.clone()))
+            .await
+            .unwrap();
+        store
+            .create(&Revision::draft("b", sample.clone()))
+            .await
+            .unwrap();
+        sample.version = 2;
+        store
+            .create(&Revision::draft("a", sample.clone()))
+            .await
+            .unwrap();
+
+        let versions = |revisions: Vec<Revision>| {
+            revisions
+                .iter()
+                .map(|revision| revision.version)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(versions(store.list("a").await.unwrap()), vec![1, 2]);
+        assert!(matches!(
+            store.create(&Revision::draft("a", sample)).await,
+            Err(StoreError::Conflict(_))
+        ));
+
+        store.publish("a", 1, Utc::now()).await.unwrap();
+        assert!(store.get("a", 1).await.unwrap().unwrap().is_published());
+        assert!(!store.get("a", 2).await.unwrap().unwrap().is_published());
+        assert!(matches!(
+            store.publish("a", 1, Utc::now()).await,
+            Err(StoreError::Conflict(_))
+        ));
+    }
+}
@@ -0,0 +1,229 @@
+use std::env;
+use std::fmt;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rusoto_core::Region;
+use uuid::Uuid;
+
+use super::qna::questionlist::QuestionList;
+use super::qna::schemacache::SchemaCache;
+
+pub mod dynamodb;
+pub mod files;
+pub mod memory;
+pub mod s3;
+
+#[derive(Debug)]
+pub enum StoreError {
+    Backend(String),
+    /// A write would have changed something that must stay as it is.
+    Conflict(String),
+    Serialization(serde_json::Error),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Backend(message) => write!(f, "storage backend error: {}", message),
+            Self::Conflict(message) => write!(f, "conflicting write: {}", message),
+            Self::Serialization(err) => write!(f, "stored document is invalid: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<serde_json::Error> for StoreError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Serialization(err)
+    }
+}
+
+pub(crate) fn backend<E: fmt::Display>(err: E) -> StoreError {
+    StoreError::Backend(err.to_string())
+}
+
+pub(crate) fn required_env(var: &str) -> Result<String, StoreError> {
+    env::var(var).map_err(|_| StoreError::Backend(format!("{} is not set", var)))
+}
+
+/// Region to talk to, honouring `endpoint_var` so a local stand-in such as
+/// DynamoDB Local or MinIO can replace the real service.
+pub fn region_from_env(endpoint_var: &str) -> Region {
+    match env::var(endpoint_var) {
+        Ok(endpoint) => Region::Custom {
+            name: env::var("AWS_REGION").unwrap_or_else(|_| "local".into()),
+            endpoint,
+        },
+        Err(_) => Region::default(),
+    }
+}
+
+/// Storage for questionnaires, keyed by questionnaire id.
+#[async_trait]
+pub trait QuestionListRepository: Send + Sync {
+    async fn get(&self, id: &str) -> Result<Option<QuestionList>, StoreError>;
+    async fn put(&self, id: &str, question_list: &QuestionList) -> Result<(), StoreError>;
+    /// Ids of all stored questionnaires, in ascending order.
+    async fn list(&self) -> Result<Vec<String>, StoreError>;
+    async fn delete(&self, id: &str) -> Result<(), StoreError>;
+}
+
+/// A validated answer payload, recorded against the version of the revision
+/// it was checked with.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Submission {
+    pub id: Uuid,
+    pub questionnaire_id: String,
+    pub version: u32,
+    pub submitted_at: DateTime<Utc>,
+    pub answers: serde_json::Value,
+}
+
+impl Submission {
+    pub fn new(questionnaire_id: &str, version: u32, answers: serde_json::Value) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            questionnaire_id: questionnaire_id.to_string(),
+            version,
+            submitted_at: Utc::now(),
+            answers,
+        }
+    }
+}
+
+/// Where a revision is in its lifecycle.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// Saved, but not yet served to takers.
+    Draft,
+    /// Served to takers; it stays published for good.
+    Published,
+}
+
+/// A saved state of a questionnaire. Its questions never change once saved;
+/// editing the questionnaire saves a new revision with the next version
+/// instead.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Revision {
+    pub questionnaire_id: String,
+    pub version: u32,
+    pub status: Status,
+    pub saved_at: DateTime<Utc>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub question_list: QuestionList,
+}
+
+impl Revision {
+    /// A draft of `question_list`, numbered by its version.
+    pub fn draft(questionnaire_id: &str, question_list: QuestionList) -> Self {
+        Self {
+            questionnaire_id: questionnaire_id.to_string(),
+            version: question_list.version,
+            status: Status::Draft,
+            saved_at: Utc::now(),
+            published_at: None,
+            question_list,
+        }
+    }
+
+    pub fn is_published(&self) -> bool {
+        self.status == Status::Published
+    }
+}
+
+/// Storage for revisions, keyed by questionnaire id and version.
+#[async_trait]
+pub trait RevisionStore: Send + Sync {
+    /// Saves a new revision, failing with `StoreError::Conflict` rather than
+    /// overwriting one with the same version.
+    async fn create(&self, revision: &Revision) -> Result<(), StoreError>;
+    async fn get(
+        &self,
+        questionnaire_id: &str,
+        version: u32,
+    ) -> Result<Option<Revision>, StoreError>;
+    /// Revisions of a questionnaire, in ascending order of version.
+    async fn list(&self, questionnaire_id: &str) -> Result<Vec<Revision>, StoreError>;
+    /// Marks a draft as published at `at`, failing with
+    /// `StoreError::Conflict` if there is no such draft.
+    async fn publish(
+        &self,
+        questionnaire_id: &str,
+        version: u32,
+        at: DateTime<Utc>,
+    ) -> Result<(), StoreError>;
+}
+
+/// Storage for submissions, keyed by questionnaire id and submission id.
+#[async_trait]
+pub trait SubmissionStore: Send + Sync {
+    async fn put(&self, submission: &Submission) -> Result<(), StoreError>;
+    async fn get(&self, questionnaire_id: &str, id: Uuid)
+        -> Result<Option<Submission>, StoreError>;
+    /// Every submission to a questionnaire, oldest first.
+    async fn list(&self, questionnaire_id: &str) -> Result<Vec<Submission>, StoreError>;
+}
+
+/// The storage backends a handler works against.
+#[derive(Clone)]
+pub struct Stores {
+    /// The latest revision of each questionnaire, whatever its status.
+    pub question_lists: Arc<dyn QuestionListRepository>,
+    pub revisions: Arc<dyn RevisionStore>,
+    pub submissions: Arc<dyn SubmissionStore>,
+    /// Schemas compiled from the stored questionnaires, shared by every
+    /// request a warm container serves.
+    pub schemas: Arc<SchemaCache>,
+}
+
+impl Stores {
+    /// DynamoDB-backed questionnaires and revisions; submissions go to the S3
+    /// bucket named by `SUBMISSION_BUCKET` when it is set, and to DynamoDB
+    /// otherwise.
+    pub fn from_env() -> Result<Self, StoreError> {
+        let submissions: Arc<dyn SubmissionStore> = if env::var("SUBMISSION_BUCKET").is_ok() {
+            Arc::new(s3::S3SubmissionStore::from_env()?)
+        } else {
+            Arc::new(dynamodb::DynamoDbSubmissionStore::from_env()?)
+        };
+        Ok(Self {
+            question_lists: Arc::new(dynamodb::DynamoDbQuestionListRepository::from_env()?),
+            revisions: Arc::new(dynamodb::DynamoDbRevisionStore::from_env()?),
+            submissions,
+            schemas: Arc::new(SchemaCache::new()),
+        })
+    }
+
+    /// In-memory stores holding the given questionnaires.
+    pub fn memory<I: IntoIterator<Item = (String, QuestionList)>>(question_lists: I) -> Self {
+        Self {
+            question_lists: Arc::new(
+                question_lists
+                    .into_iter()
+                    .collect::<memory::MemoryQuestionListRepository>(),
+            ),
+            revisions: Arc::new(memory::MemoryRevisionStore::new()),
+            submissions: Arc::new(memory::MemorySubmissionStore::new()),
+            schemas: Arc::new(SchemaCache::new()),
+        }
+    }
+
+    /// Questionnaires read from the files in `dir`, with revisions and
+    /// submissions kept in memory; what the local development server runs
+    /// against. Files can be edited without bumping their version, so their
+    /// schemas are compiled afresh for every submission.
+    pub fn files(dir: impl Into<std::path::PathBuf>) -> Self {
+        Self {
+            question_lists: Arc::new(files::FileQuestionListRepository::new(dir)),
+            revisions: Arc::new(memory::MemoryRevisionStore::new()),
+            submissions: Arc::new(memory::MemorySubmissionStore::new()),
+            schemas: Arc::new(SchemaCache::bypassed()),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use async_trait::async_trait;
+use rusoto_core::RusotoError;
+use rusoto_s3::{
+    GetObjectError, GetObjectRequest, ListObjectsV2Request, PutObjectRequest, S3Client, S3,
+};
+use tokio::io::AsyncReadExt;
+use uuid::Uuid;
+
+use super::{backend, region_from_env, required_env, StoreError, Submission, SubmissionStore};
+
+/// Environment variable pointing the S3 client at a local stand-in such as
+/// MinIO or LocalStack.
+pub const ENDPOINT_VAR: &str = "S3_ENDPOINT";
+
+/// Stores each submission as a JSON object at
+/// `{questionnaire_id}/{submission_id}.json`.
+pub struct S3SubmissionStore {
+    client: S3Client,
+    bucket: String,
+}
+
+impl S3SubmissionStore {
+    pub fn new(client: S3Client, bucket: impl Into<String>) -> Self {
+        Self {
+            client,
+            bucket: bucket.into(),
+        }
+    }
+
+    /// Builds a store for the bucket named by `SUBMISSION_BUCKET`.
+    pub fn from_env() -> Result<Self, StoreError> {
+        Ok(Self::new(
+            S3Client::new(region_from_env(ENDPOINT_VAR)),
+            required_env("SUBMISSION_BUCKET")?,
+        ))
+    }
+
+    fn key(questionnaire_id: &str, id: Uuid) -> String {
+        format!("{}/{}.json", questionnaire_id, id)
+    }
+}
+
+#[async_trait]
+impl SubmissionStore for S3SubmissionStore {
+    async fn put(&self, submission: &Submission) -> Result<(), StoreError> {
+        self.client
+            .put_object(PutObjectRequest {
+                bucket: self.bucket.clone(),
+                key: Self::key(&submission.questionnaire_id, submission.id),
+                body: Some(serde_json::to_vec(submission)?.into()),
+                content_type: Some("application/json".into()),
+                ..Default::default()
+            })
+            .await
+            .map_err(backend)?;
+        Ok(())
+    }
+
+    async fn get(
+        &self,
+        questionnaire_id: &str,
+        id: Uuid,
+    ) -> Result<Option<Submission>, StoreError> {
+        let output = match self
+            .client
+            .get_object(GetObjectRequest {
+                bucket: self.bucket.clone(),
+                key: Self::key(questionnaire_id, id),
+                ..Default::default()
+            })
+            .await
+        {
+            Ok(output) => output,
+            Err(RusotoError::Service(GetObjectError::NoSuchKey(_))) => return Ok(None),
+            Err(err) => return Err(backend(err)),
+        };
+
+        let mut document = Vec::new();
+        if let Some(body) = output.body {
+            body.into_async_read()
+                .read_to_end(&mut document)
+                .await
+                .map_err(backend)?;
+        }
+        Ok(Some(serde_json::from_slice(&document)?))
+    }
+
+    async fn list(&self, questionnaire_id: &str) -> Result<Vec<Submission>, StoreError> {
+        let prefix = format!("{}/", questionnaire_id);
+        let mut ids = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let output = self
+                .client
+                .list_objects_v2(ListObjectsV2Request {
+                    bucket: self.bucket.clone(),
+                    prefix: Some(prefix.clone()),
+                    continuation_token,
+                    ..Default::default()
+                })
+                .await
+                .map_err(backend)?;
+
+            // Objects not named `{submission_id}.json` were not put here.
+            ids.extend(
+                output
+                    .contents
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|object| object.key)
+                    .filter_map(|key| {
+                        key.strip_prefix(&prefix)?
+                            .strip_suffix(".json")?
+                            .parse::<Uuid>()
+                            .ok()
+                    }),
+            );
+
+            match output.next_continuation_token {
+                Some(token) if output.is_truncated == Some(true) => {
+                    continuation_token = Some(token)
+                }
+                _ => break,
+            }
+        }
+
+        let mut submissions = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(submission) = self.get(questionnaire_id, id).await? {
+                submissions.push(submission);
+            }
+        }
+        submissions.sort_by_key(|s: &Submission| s.submitted_at);
+        Ok(submissions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusoto_s3::CreateBucketRequest;
+    use serde_json::json;
+
+    // Needs an S3 stand-in, e.g.
+    // `docker run -p 9000:9000 minio/minio server /data` and
+    // `S3_ENDPOINT=http://localhost:9000 cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn round_trip_against_local_s3() {
+        let client = S3Client::new(region_from_env(ENDPOINT_VAR));
+        let bucket = format!("submissions-{}", Uuid::new_v4());
+        client
+            .create_bucket(CreateBucketRequest {
+                bucket: bucket.clone(),
+                ..Default::default()
+            })
+            .await
+            .expect("create bucket");
+
+        let store = S3SubmissionStore::new(client, bucket);
+        let submission = Submission::new("sample", 1, json!({ "3": "Ice" }));
+
+        store.put(&submission).await.unwrap();
+        assert_eq!(
+            store.get("sample", submission.id).await.unwrap(),
+            Some(submission.clone())
+        );
+        assert_eq!(store.list("sample").await.unwrap(), vec![submission]);
+        assert!(store.get("sample", Uuid::new_v4()).await.unwrap().is_none());
+    }
+}